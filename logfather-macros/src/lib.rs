@@ -0,0 +1,265 @@
+//! # Logfather Macros
+//!
+//! Procedural macros for [logfather](https://crates.io/crates/logfather), re-exported by it with the `instrument`
+//! feature. Depend on logfather rather than on this crate directly.
+#![allow(clippy::needless_return)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Expr, FnArg, Ident, ItemFn, Lit, Meta, Pat, ReturnType, Token, Type,
+};
+
+/// Logs the entry of a function with its arguments, and its exit with its return value and elapsed time.
+///
+/// Arguments and the return value are logged with their `Debug` implementation, so arguments which don't implement
+/// it, or are sensitive or huge, must be skipped. `self` and destructured arguments are never logged, and neither is
+/// a `()` return value. Synchronous functions enter a span, so messages logged inside them show up under the
+/// function's name in the `{span}` placeholder. Async functions log their entry when first polled and their exit
+/// when they complete, but do not enter a span since spans cannot move between threads.
+///
+/// # Arguments
+/// * `level` - The level of the entry and exit messages, as a name such as `"debug"` or a `Level` expression -
+///   `Info` by default.
+/// * `skip(...)` - Arguments to leave out of the entry message, and `return` to leave out the return value.
+///
+/// # Examples
+///
+/// ``` ignore
+/// use logfather::*;
+///
+/// #[logfather::instrument(level = "debug", skip(password))]
+/// fn login(user: &str, password: &str) -> Result<u32, String> {
+///     // ...
+/// } // [... DEBUG app] enter login user="alice"
+///   // [... DEBUG app] exit login return=Ok(42) elapsed=1.2ms
+///
+/// #[logfather::instrument(skip(return))]
+/// async fn fetch(url: String) -> Vec<u8> {
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn instrument(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as Args);
+    let function = parse_macro_input!(item as ItemFn);
+
+    return match expand(args, function) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    };
+}
+
+/// The settings given to `#[instrument(...)]`.
+struct Args {
+    level: Option<TokenStream2>,
+    skip: Vec<Ident>,
+    skip_return: bool,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Args { level: None, skip: vec![], skip_return: false };
+
+        for meta in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            match meta {
+                Meta::NameValue(value) if value.path.is_ident("level") => {
+                    args.level = Some(level(&value.value)?);
+                }
+                Meta::List(list) if list.path.is_ident("skip") => {
+                    for skipped in list.parse_args_with(Punctuated::<Skipped, Token![,]>::parse_terminated)? {
+                        match skipped {
+                            Skipped::Argument(ident) => args.skip.push(ident),
+                            Skipped::Return => args.skip_return = true,
+                        }
+                    }
+                }
+                other => {
+                    return Err(syn::Error::new(other.span(), "expected `level = ...` or `skip(...)`"));
+                }
+            }
+        }
+        return Ok(args);
+    }
+}
+
+/// An entry of `skip(...)` - an argument name or `return`.
+enum Skipped {
+    Argument(Ident),
+    Return,
+}
+
+impl Parse for Skipped {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![return]) {
+            input.parse::<Token![return]>()?;
+            return Ok(Skipped::Return);
+        }
+        return Ok(Skipped::Argument(Ident::parse_any(input)?));
+    }
+}
+
+/// Resolves a level name such as `"debug"` to its `Level`, and passes any other expression through.
+fn level(expr: &Expr) -> syn::Result<TokenStream2> {
+    let Expr::Lit(literal) = expr else {
+        return Ok(expr.to_token_stream());
+    };
+    let Lit::Str(name) = &literal.lit else {
+        return Err(syn::Error::new(literal.span(), "expected a level name or a `Level` expression"));
+    };
+
+    let variant = match name.value().to_lowercase().as_str() {
+        "trace" => quote!(Trace),
+        "debug" => quote!(Debug),
+        "info" => quote!(Info),
+        "warn" | "warning" => quote!(Warning),
+        "error" => quote!(Error),
+        "crit" | "critical" => quote!(Critical),
+        "diag" | "diagnostic" => quote!(Diagnostic),
+        "notice" => quote!(NOTICE),
+        "audit" => quote!(AUDIT),
+        "fatal" => quote!(FATAL),
+        _ => {
+            return Err(syn::Error::new(
+                name.span(),
+                "unknown level - expected trace, debug, info, warning, error, critical, diagnostic, notice, audit or fatal",
+            ))
+        }
+    };
+    return Ok(quote!(::logfather::Level::#variant));
+}
+
+fn expand(args: Args, function: ItemFn) -> syn::Result<TokenStream2> {
+    let ItemFn { attrs, vis, sig, block } = function;
+    let name = sig.ident.unraw().to_string();
+    let level = args.level.unwrap_or_else(|| quote!(::logfather::Level::Info));
+
+    // Every skipped name must be an argument, so a typo can't leak a password into the logs
+    let arguments: Vec<Ident> = sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(typed) => match &*typed.pat {
+                Pat::Ident(pat) => Some(pat.ident.clone()),
+                _ => None,
+            },
+            FnArg::Receiver(_) => None,
+        })
+        .collect();
+    if let Some(unknown) = args.skip.iter().find(|skipped| !arguments.contains(skipped)) {
+        return Err(syn::Error::new(unknown.span(), format!("`{}` is not an argument of `{}`", unknown, name)));
+    }
+
+    let fields = arguments.iter().filter(|argument| !args.skip.contains(argument)).map(|argument| {
+        let key = argument.unraw().to_string();
+        quote!((#key, ::logfather::kv::Value::Debug(&#argument)))
+    });
+
+    // Annotating the result keeps `?` in the body inferrable - impossible for `impl Trait`, which can't be named here
+    let (annotation, unit) = match &sig.output {
+        ReturnType::Default => (quote!(: ()), true),
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::ImplTrait(_) => (quote!(), false),
+            Type::Tuple(tuple) if tuple.elems.is_empty() => (quote!(: #ty), true),
+            ty => (quote!(: #ty), false),
+        },
+    };
+    let record_return = !args.skip_return && !unit;
+
+    if sig.asyncness.is_some() {
+        // Formatting the return value is skipped when its exit won't be logged anyway
+        let returned = if record_return {
+            quote! {
+                if ::logfather::logger::enabled(&__logfather_level) {
+                    ::core::option::Option::Some(::std::format!("{:?}", __logfather_return))
+                } else {
+                    ::core::option::Option::None
+                }
+            }
+        } else {
+            quote!(::core::option::Option::None)
+        };
+
+        return Ok(quote! {
+            #(#attrs)* #vis #sig {
+                let __logfather_level: ::logfather::Level = #level;
+                let __logfather_start = ::std::time::Instant::now();
+                ::logfather::span::log_entry(
+                    __logfather_level.clone(),
+                    ::core::module_path!(),
+                    #name,
+                    &[#(#fields),*],
+                    ::core::panic::Location::caller(),
+                );
+                let __logfather_return #annotation = async move #block.await;
+                let __logfather_returned: ::core::option::Option<::std::string::String> = #returned;
+                ::logfather::span::log_exit(
+                    __logfather_level,
+                    ::core::module_path!(),
+                    #name,
+                    __logfather_start.elapsed(),
+                    __logfather_returned.as_ref().map(|value| value as &dyn ::core::fmt::Display),
+                    ::core::panic::Location::caller(),
+                );
+                __logfather_return
+            }
+        });
+    }
+
+    let closure_output = match &sig.output {
+        ReturnType::Type(_, ty) if !matches!(**ty, Type::ImplTrait(_)) => quote!(-> #ty),
+        _ => quote!(),
+    };
+    let (binding, record) = if record_return {
+        (quote!(mut __logfather_span), quote!(__logfather_span.returned(&__logfather_return);))
+    } else {
+        (quote!(__logfather_span), quote!())
+    };
+
+    return Ok(quote! {
+        #(#attrs)* #vis #sig {
+            let #binding = ::logfather::span::Span::enter_fields(#level, ::core::module_path!(), #name, &[#(#fields),*]);
+            #[allow(clippy::redundant_closure_call)]
+            let __logfather_return #annotation = (|| #closure_output #block)();
+            #record
+            __logfather_return
+        }
+    });
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args: Args = syn::parse_str("level = \"WARN\", skip(password, r#type, return)").unwrap();
+        assert_eq!(args.level.unwrap().to_string(), quote!(::logfather::Level::Warning).to_string());
+        assert_eq!(args.skip, vec!["password", "r#type"]);
+        assert!(args.skip_return);
+
+        let args: Args = syn::parse_str("level = \"audit\"").unwrap();
+        assert_eq!(args.level.unwrap().to_string(), quote!(::logfather::Level::AUDIT).to_string());
+
+        let args: Args = syn::parse_str("level = Level::Custom(150)").unwrap();
+        assert_eq!(args.level.unwrap().to_string(), quote!(Level::Custom(150)).to_string());
+
+        assert!(syn::parse_str::<Args>("level = \"loud\"").is_err());
+        assert!(syn::parse_str::<Args>("skip_all").is_err());
+    }
+
+    #[test]
+    fn test_unknown_skip_is_rejected() {
+        let function: ItemFn = syn::parse_str("fn login(user: &str, password: &str) {}").unwrap();
+        let error = expand(syn::parse_str("skip(pasword)").unwrap(), function).unwrap_err();
+        assert_eq!(error.to_string(), "`pasword` is not an argument of `login`");
+    }
+}
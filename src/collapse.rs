@@ -0,0 +1,284 @@
+use crate::logger::Level;
+use std::{
+    collections::HashMap,
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// Work run by the collapse timer once it is due.
+pub(crate) type Task = Box<dyn FnOnce() + Send>;
+
+lazy_static::lazy_static! {
+    // One thread writes every summary whose timeout expires without another message arriving
+    static ref TIMER: Mutex<Sender<(Instant, Task)>> = Mutex::new(timer());
+}
+
+/// The outputs that can collapse repeated messages independently of one another.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub(crate) enum Output {
+    Terminal,
+    File,
+}
+
+/// A finished run of repeated messages which still needs its summary line written.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Pending {
+    pub(crate) level: Level,
+    pub(crate) module_path: String,
+    pub(crate) repeats: u64,
+}
+
+impl Pending {
+    /// The summary line written in place of the collapsed messages.
+    pub(crate) fn message(&self) -> String {
+        return format!("last message repeated {} times", self.repeats);
+    }
+}
+
+/// The outcome of passing a message through the collapser.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Observed {
+    /// The message repeats the current run and should not be written.
+    Repeat,
+    /// The message is the first repeat of the current run and should not be written - the run's summary is due at
+    /// the given instant, when its timeout expires.
+    FirstRepeat(Instant),
+    /// The message starts a new run - any summary of the previous run must be written first.
+    New(Option<Pending>),
+}
+
+#[derive(Debug)]
+struct Run {
+    level: Level,
+    module_path: String,
+    message: String,
+    repeats: u64,
+    since: Instant,
+}
+
+impl Run {
+    fn pending(&self) -> Option<Pending> {
+        if self.repeats == 0 {
+            return None;
+        }
+
+        return Some(Pending {
+            level: self.level.clone(),
+            module_path: self.module_path.clone(),
+            repeats: self.repeats,
+        });
+    }
+}
+
+/// The current run of every output of a logger.
+#[derive(Debug, Default)]
+pub(crate) struct Collapser {
+    runs: HashMap<Output, Run>,
+}
+
+impl Collapser {
+    fn observe(
+        &mut self,
+        output: Output,
+        level: &Level,
+        module_path: &str,
+        message: &str,
+        timeout: Duration,
+        now: Instant,
+    ) -> Observed {
+        if let Some(run) = self.runs.get_mut(&output) {
+            let same = run.level == *level && run.module_path == module_path && run.message == message;
+            if same && now.saturating_duration_since(run.since) < timeout {
+                run.repeats += 1;
+                return match run.repeats {
+                    1 => Observed::FirstRepeat(run.since + timeout),
+                    _ => Observed::Repeat,
+                };
+            }
+        }
+
+        let previous = self.runs.insert(
+            output,
+            Run {
+                level: level.clone(),
+                module_path: module_path.to_string(),
+                message: message.to_string(),
+                repeats: 0,
+                since: now,
+            },
+        );
+
+        return Observed::New(previous.and_then(|run| run.pending()));
+    }
+
+    fn drain(&mut self, output: Output) -> Option<Pending> {
+        return self.runs.remove(&output).and_then(|run| run.pending());
+    }
+
+    fn expire(&mut self, timeout: Duration, now: Instant) -> Vec<(Output, Pending)> {
+        let expired: Vec<Output> = self
+            .runs
+            .iter()
+            .filter(|(_, run)| now.saturating_duration_since(run.since) >= timeout)
+            .map(|(output, _)| *output)
+            .collect();
+        return expired.into_iter().filter_map(|output| Some((output, self.drain(output)?))).collect();
+    }
+}
+
+/// Passes a message destined for `output` through a logger's collapser.
+///
+/// # Arguments
+/// * `runs` - The collapser of the logger writing the message.
+/// * `output` - The output the message is about to be written to.
+/// * `level` - The severity level of the message.
+/// * `module_path` - The module path the message originates from.
+/// * `message` - The formatted log message.
+/// * `timeout` - How long a run may last before it is summarized and restarted.
+pub(crate) fn observe(
+    runs: &Mutex<Collapser>,
+    output: Output,
+    level: &Level,
+    module_path: &str,
+    message: &str,
+    timeout: Duration,
+) -> Observed {
+    return match runs.lock() {
+        Ok(mut runs) => runs.observe(output, level, module_path, message, timeout, Instant::now()),
+        // Never drop messages because the collapser itself is unavailable
+        Err(_) => Observed::New(None),
+    };
+}
+
+/// Ends the current run for `output`, returning its summary if any messages were collapsed.
+pub(crate) fn drain(runs: &Mutex<Collapser>, output: Output) -> Option<Pending> {
+    return runs.lock().ok().and_then(|mut runs| runs.drain(output));
+}
+
+/// Ends every run which lasted `timeout` or longer, returning the summaries of those which collapsed messages.
+pub(crate) fn expire(runs: &Mutex<Collapser>, timeout: Duration) -> Vec<(Output, Pending)> {
+    return match runs.lock() {
+        Ok(mut runs) => runs.expire(timeout, Instant::now()),
+        Err(_) => vec![],
+    };
+}
+
+/// Runs `task` on the collapse timer thread once `due` has passed.
+pub(crate) fn schedule(due: Instant, task: Task) {
+    if let Ok(timer) = TIMER.lock() {
+        _ = timer.send((due, task));
+    }
+}
+
+fn timer() -> Sender<(Instant, Task)> {
+    let (sender, receiver) = mpsc::channel::<(Instant, Task)>();
+    // Should the thread fail to start, summaries are left to the next message or `flush()`
+    _ = std::thread::Builder::new().name(String::from("logfather-collapse")).spawn(move || {
+        let mut tasks: Vec<(Instant, Task)> = vec![];
+        loop {
+            let received = match tasks.iter().map(|(due, _)| *due).min() {
+                Some(due) => receiver.recv_timeout(due.saturating_duration_since(Instant::now())),
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(task) => tasks.push(task),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            let now = Instant::now();
+            let (due, waiting): (Vec<_>, Vec<_>) = tasks.into_iter().partition(|(due, _)| *due <= now);
+            tasks = waiting;
+            for (_, task) in due {
+                task();
+            }
+        }
+    });
+    return sender;
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn test_repeats_are_collapsed() {
+        let mut collapser = Collapser::default();
+        let now = Instant::now();
+
+        let first = collapser.observe(Output::File, &Level::Info, "app", "hello", TIMEOUT, now);
+        assert_eq!(first, Observed::New(None));
+        let repeat = collapser.observe(Output::File, &Level::Info, "app", "hello", TIMEOUT, now);
+        assert_eq!(repeat, Observed::FirstRepeat(now + TIMEOUT));
+        for _ in 0..2 {
+            let repeat = collapser.observe(Output::File, &Level::Info, "app", "hello", TIMEOUT, now);
+            assert_eq!(repeat, Observed::Repeat);
+        }
+
+        let next = collapser.observe(Output::File, &Level::Info, "app", "goodbye", TIMEOUT, now);
+        let expected = Pending {
+            level: Level::Info,
+            module_path: String::from("app"),
+            repeats: 3,
+        };
+        assert_eq!(next, Observed::New(Some(expected)));
+    }
+
+    #[test]
+    fn test_level_and_module_break_runs() {
+        let mut collapser = Collapser::default();
+        let now = Instant::now();
+
+        collapser.observe(Output::File, &Level::Info, "app", "hello", TIMEOUT, now);
+        let level = collapser.observe(Output::File, &Level::Error, "app", "hello", TIMEOUT, now);
+        assert_eq!(level, Observed::New(None));
+        let module = collapser.observe(Output::File, &Level::Error, "app::net", "hello", TIMEOUT, now);
+        assert_eq!(module, Observed::New(None));
+    }
+
+    #[test]
+    fn test_timeout_restarts_run() {
+        let mut collapser = Collapser::default();
+        let now = Instant::now();
+
+        collapser.observe(Output::Terminal, &Level::Info, "app", "hello", TIMEOUT, now);
+        collapser.observe(Output::Terminal, &Level::Info, "app", "hello", TIMEOUT, now);
+
+        let later = now + TIMEOUT;
+        let restarted = collapser.observe(Output::Terminal, &Level::Info, "app", "hello", TIMEOUT, later);
+        assert!(matches!(restarted, Observed::New(Some(Pending { repeats: 1, .. }))));
+    }
+
+    #[test]
+    fn test_outputs_are_independent() {
+        let mut collapser = Collapser::default();
+        let now = Instant::now();
+
+        collapser.observe(Output::Terminal, &Level::Info, "app", "hello", TIMEOUT, now);
+        let file = collapser.observe(Output::File, &Level::Info, "app", "hello", TIMEOUT, now);
+        assert_eq!(file, Observed::New(None));
+        assert_eq!(collapser.drain(Output::Terminal), None);
+    }
+
+    #[test]
+    fn test_expired_runs_are_summarized() {
+        let mut collapser = Collapser::default();
+        let now = Instant::now();
+
+        collapser.observe(Output::Terminal, &Level::Info, "app", "hello", TIMEOUT, now);
+        collapser.observe(Output::Terminal, &Level::Info, "app", "hello", TIMEOUT, now);
+        collapser.observe(Output::File, &Level::Info, "app", "hello", TIMEOUT, now + TIMEOUT / 2);
+        assert!(collapser.expire(TIMEOUT, now + TIMEOUT / 2).is_empty());
+
+        let expired = collapser.expire(TIMEOUT, now + TIMEOUT);
+        assert!(matches!(expired.as_slice(), [(Output::Terminal, Pending { repeats: 1, .. })]));
+        assert!(collapser.runs.contains_key(&Output::File) && !collapser.runs.contains_key(&Output::Terminal));
+    }
+}
@@ -0,0 +1,451 @@
+use crate::{
+    error::*,
+    logger::{Level, Logger, TimeZone},
+    rate_limit::RateLimit,
+    sample::SampleRule,
+};
+use dekor::Style;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::BTreeMap, path::PathBuf, time::Duration};
+
+const STYLES: [(&str, Style); 22] = [
+    ("Reset", Style::Reset),
+    ("Bold", Style::Bold),
+    ("Italic", Style::Italic),
+    ("Underline", Style::Underline),
+    ("FGBlack", Style::FGBlack),
+    ("FGRed", Style::FGRed),
+    ("FGGreen", Style::FGGreen),
+    ("FGYellow", Style::FGYellow),
+    ("FGBlue", Style::FGBlue),
+    ("FGPurple", Style::FGPurple),
+    ("FGCyan", Style::FGCyan),
+    ("FGWhite", Style::FGWhite),
+    ("FGRGB", Style::FGRGB),
+    ("BGBlack", Style::BGBlack),
+    ("BGRed", Style::BGRed),
+    ("BGGreen", Style::BGGreen),
+    ("BGYellow", Style::BGYellow),
+    ("BGBlue", Style::BGBlue),
+    ("BGPurple", Style::BGPurple),
+    ("BGCyan", Style::BGCyan),
+    ("BGWhite", Style::BGWhite),
+    ("BGRGB", Style::BGRGB),
+];
+
+/// Parses a terminal style from its variant name, ignoring case.
+pub(crate) fn parse_style(name: &str) -> Result<Style, LogfatherError> {
+    return STYLES
+        .iter()
+        .find(|(label, _)| label.eq_ignore_ascii_case(name.trim()))
+        .map(|(_, style)| *style)
+        .ok_or_else(|| {
+            let names: Vec<&str> = STYLES.iter().map(|(label, _)| *label).collect();
+            LogfatherError::ConfigError(format!("unknown style `{}`, expected one of {}", name, names.join(", ")))
+        });
+}
+
+fn style_name(style: &Style) -> &'static str {
+    return STYLES.iter().find(|(_, s)| s == style).map(|(label, _)| *label).unwrap_or("Reset");
+}
+
+impl Serialize for Level {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(&self.to_string());
+    }
+}
+
+impl<'de> Deserialize<'de> for Level {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        return name.parse().map_err(|e: LogfatherError| de::Error::custom(config_message(e)));
+    }
+}
+
+/// A terminal style serialized by its variant name.
+struct StyleName(Style);
+
+impl Serialize for StyleName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(style_name(&self.0));
+    }
+}
+
+impl<'de> Deserialize<'de> for StyleName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        return parse_style(&name).map(StyleName).map_err(|e| de::Error::custom(config_message(e)));
+    }
+}
+
+/// Strips the `Invalid configuration:` prefix, which the deserializer's own error already implies.
+fn config_message(error: LogfatherError) -> String {
+    return match error {
+        LogfatherError::ConfigError(message) => message,
+        error => error.to_string(),
+    };
+}
+
+/// Settings of a single output.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OutputConfig {
+    /// Defaults to on for the terminal and, for the file, to whether a path is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<PathBuf>,
+    ignore: Vec<Level>,
+    collapse: bool,
+}
+
+/// The serialized form of a `Logger` - every setting except filters and formatters, which are code.
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LoggerConfig {
+    level: Level,
+    ignore: Vec<Level>,
+    log_format: String,
+    timestamp_format: String,
+    timezone: TimeZone,
+    /// Seconds
+    #[serde(deserialize_with = "seconds")]
+    collapse_timeout: f64,
+    context_fields: bool,
+    backtrace: Level,
+    #[serde(deserialize_with = "terminal_output")]
+    terminal: OutputConfig,
+    file: OutputConfig,
+    styles: BTreeMap<Level, Vec<StyleName>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rate_limit: Option<RateLimit>,
+    sample: Vec<SampleRule>,
+}
+
+/// Deserializes a non-negative number of seconds.
+fn seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let seconds = f64::deserialize(deserializer)?;
+    if !(seconds.is_finite() && seconds >= 0.0) {
+        return Err(de::Error::custom(format!("`collapse_timeout` must be a non-negative number of seconds, got {}", seconds)));
+    }
+    return Ok(seconds);
+}
+
+/// Deserializes the terminal's settings, which can't include a path.
+fn terminal_output<'de, D: Deserializer<'de>>(deserializer: D) -> Result<OutputConfig, D::Error> {
+    let output = OutputConfig::deserialize(deserializer)?;
+    if output.path.is_some() {
+        return Err(de::Error::custom("`terminal.path` is not supported, only the file output has a path"));
+    }
+    return Ok(output);
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        return Self::from(&Logger::new());
+    }
+}
+
+impl From<&Logger> for LoggerConfig {
+    fn from(logger: &Logger) -> Self {
+        return Self {
+            level: logger.output_level.clone(),
+            ignore: logger.ignore.clone(),
+            log_format: logger.log_format.clone(),
+            timestamp_format: logger.timestamp_format.clone(),
+            timezone: logger.timezone.clone(),
+            collapse_timeout: logger.collapse_timeout.as_secs_f64(),
+            context_fields: logger.context_fields,
+            backtrace: logger.backtrace_level.clone(),
+            terminal: OutputConfig {
+                enabled: Some(logger.terminal_output),
+                path: None,
+                ignore: logger.terminal_ignore.clone(),
+                collapse: logger.terminal_collapse,
+            },
+            file: OutputConfig {
+                enabled: Some(logger.file_output),
+                path: logger.path.clone(),
+                ignore: logger.file_ignore.clone(),
+                collapse: logger.file_collapse,
+            },
+            styles: logger
+                .styles
+                .iter()
+                .map(|(level, styles)| (level.clone(), styles.iter().copied().map(StyleName).collect()))
+                .collect(),
+            rate_limit: logger.rate_limit.clone(),
+            sample: logger.sampling.clone(),
+        };
+    }
+}
+
+impl From<LoggerConfig> for Logger {
+    fn from(config: LoggerConfig) -> Self {
+        let mut logger = Logger::new();
+        logger.output_level = config.level;
+        logger.ignore = config.ignore;
+        logger.log_format = config.log_format;
+        logger.timestamp_format = config.timestamp_format;
+        logger.timezone = config.timezone;
+        logger.collapse_timeout = Duration::from_secs_f64(config.collapse_timeout);
+        logger.context_fields = config.context_fields;
+        logger.backtrace_level = config.backtrace;
+        logger.terminal_output = config.terminal.enabled.unwrap_or(true);
+        logger.terminal_ignore = config.terminal.ignore;
+        logger.terminal_collapse = config.terminal.collapse;
+        logger.file_output = config.file.enabled.unwrap_or(config.file.path.is_some());
+        logger.path = config.file.path;
+        logger.file_ignore = config.file.ignore;
+        logger.file_collapse = config.file.collapse;
+        // Levels left out keep their default styles
+        for (level, styles) in config.styles {
+            logger.styles.insert(level, styles.into_iter().map(|style| style.0).collect());
+        }
+        logger.rate_limit = config.rate_limit;
+        logger.sampling = config.sample;
+        return logger;
+    }
+}
+
+/// Serializes every configurable setting - filters and formatters are code and are left out.
+impl Serialize for Logger {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return LoggerConfig::from(self).serialize(serializer);
+    }
+}
+
+/// Deserializes a standalone configuration - settings left out keep their defaults. Install it with `set_logger`.
+impl<'de> Deserialize<'de> for Logger {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        return LoggerConfig::deserialize(deserializer).map(Logger::from);
+    }
+}
+
+#[cfg(feature = "toml")]
+impl Logger {
+    /// Loads a logger configuration from a TOML file, with overrides from `LOGFATHER_*` environment variables.
+    ///
+    /// Settings left out of the file keep their defaults. Errors in the file are reported with their line and column.
+    /// Every environment variable named `LOGFATHER_<KEY>` then replaces the setting `<KEY>`, with `__` separating
+    /// nested keys - `LOGFATHER_LEVEL=warn` or `LOGFATHER_FILE__PATH=/var/log/app.log`. Values are read as TOML
+    /// where possible (`true`, `30`, `["debug"]`) and as plain strings otherwise. Variables which don't name a setting,
+    /// such as `LOGFATHER_HOME`, are ignored.
+    ///
+    /// The returned logger is not installed - pass it to `set_logger` to make it the global logger.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the TOML file.
+    ///
+    /// # Examples
+    ///
+    /// ```toml
+    /// level = "info"
+    /// log_format = "[{timestamp} {level} {module_path}] {message}"
+    /// timezone = "utc"
+    ///
+    /// [terminal]
+    /// ignore = ["debug"]
+    ///
+    /// [file]
+    /// path = "logs/app.log"
+    /// collapse = true
+    ///
+    /// [styles]
+    /// warning = ["Bold", "FGYellow"]
+    /// ```
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let logger = Logger::from_file("logfather.toml").expect("Invalid logger configuration");
+    /// logfather::logger::set_logger(&logger);
+    /// ```
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Logger, LogfatherError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(LogfatherError::from)?;
+        return from_toml(&text, std::env::vars())
+            .map_err(|e| LogfatherError::ConfigError(format!("{}: {}", path.display(), config_message(e))));
+    }
+}
+
+/// The settings `LOGFATHER_*` variables may override, with `__` separating nested keys. Every key of `styles` and
+/// `rate_limit` may be overridden too.
+#[cfg(feature = "toml")]
+const ENV_KEYS: [&str; 21] = [
+    "level",
+    "ignore",
+    "log_format",
+    "timestamp_format",
+    "timezone",
+    "collapse_timeout",
+    "context_fields",
+    "backtrace",
+    "terminal",
+    "terminal__enabled",
+    "terminal__path",
+    "terminal__ignore",
+    "terminal__collapse",
+    "file",
+    "file__enabled",
+    "file__path",
+    "file__ignore",
+    "file__collapse",
+    "styles",
+    "rate_limit",
+    "sample",
+];
+
+/// Whether an environment variable names a setting - `LOGFATHER_FILE__PATH` does, `LOGFATHER_HOME` does not.
+#[cfg(feature = "toml")]
+fn is_setting(name: &str) -> bool {
+    let Some(key) = name.strip_prefix("LOGFATHER_").map(str::to_lowercase) else {
+        return false;
+    };
+    return match key.strip_prefix("styles__").or(key.strip_prefix("rate_limit__")) {
+        Some(rest) => !rest.contains("__"),
+        None => ENV_KEYS.contains(&key.as_str()),
+    };
+}
+
+/// Parses a TOML configuration and applies the `LOGFATHER_*` overrides found in `vars`.
+#[cfg(feature = "toml")]
+pub(crate) fn from_toml(text: &str, vars: impl Iterator<Item = (String, String)>) -> Result<Logger, LogfatherError> {
+    // Validate the file on its own first, so its errors carry a line and column
+    let logger: Logger = toml::from_str(text).map_err(|e| LogfatherError::ConfigError(e.to_string()))?;
+
+    let overrides: Vec<(String, String)> = vars.filter(|(name, _)| is_setting(name)).collect();
+    if overrides.is_empty() {
+        return Ok(logger);
+    }
+
+    let mut table: toml::Table = text.parse().map_err(|e: toml::de::Error| LogfatherError::ConfigError(e.to_string()))?;
+    for (name, value) in &overrides {
+        let keys: Vec<String> = name["LOGFATHER_".len()..].split("__").map(str::to_lowercase).collect();
+        let (last, parents) = keys.split_last().expect("split always yields a key");
+
+        let mut current = &mut table;
+        for key in parents {
+            let entry = current.entry(key.clone()).or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            current = match entry {
+                toml::Value::Table(table) => table,
+                _ => return Err(LogfatherError::ConfigError(format!("{}: `{}` is not a table", name, key))),
+            };
+        }
+        current.insert(last.clone(), env_value(value));
+    }
+
+    let names: Vec<&str> = overrides.iter().map(|(name, _)| name.as_str()).collect();
+    return Logger::deserialize(toml::Value::Table(table))
+        .map_err(|e| LogfatherError::ConfigError(format!("{} (from {})", e.message().trim_end(), names.join(", "))));
+}
+
+/// Reads an environment variable as a TOML value, falling back to a plain string.
+#[cfg(feature = "toml")]
+fn env_value(value: &str) -> toml::Value {
+    return match format!("value = {}", value).parse::<toml::Table>() {
+        Ok(mut table) => table.remove("value").unwrap_or_else(|| toml::Value::String(value.to_string())),
+        Err(_) => toml::Value::String(value.to_string()),
+    };
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RateLimitKey, SampleRate};
+    use simplicio::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut logger = Logger::standalone()
+            .level(Level::Warning)
+            .ignore(Level::Error)
+            .file(true)
+            .path("logs/app.log")
+            .file_ignore(Level::Trace)
+            .terminal_collapse(true)
+            .timezone(TimeZone::Utc)
+            .rate_limit(RateLimit::new(5, 20).key(RateLimitKey::Message))
+            .sample(SampleRule::new(SampleRate::OneIn(10)).level(Level::Trace).module("app::net"))
+            .backtrace(Level::Critical);
+        logger.style(Level::Info, vec![Style::Bold, Style::FGCyan]);
+
+        let text = toml::to_string(&logger).unwrap();
+        let parsed: Logger = toml::from_str(&text).unwrap();
+        assert_eq!(toml::to_string(&parsed).unwrap(), text);
+        assert_eq!(parsed.styles(Level::Info), vec![Style::Bold, Style::FGCyan]);
+        assert_eq!(parsed.path, Some(PathBuf::from("logs/app.log")));
+    }
+
+    #[test]
+    fn test_names_are_case_insensitive() {
+        let parsed: Logger = toml::from_str("level = \"warn\"\n[styles]\nerror = [\"bold\", \"bgred\"]").unwrap();
+
+        assert_eq!(parsed.output_level, Level::Warning);
+        assert_eq!(parsed.styles(Level::Error), vec![Style::Bold, Style::BGRed]);
+        assert_eq!(parsed.styles(Level::Info), vec![Style::FGGreen]);
+        assert!(parsed.terminal_output && !parsed.file_output);
+
+        let parsed: Logger = toml::from_str("[file]\npath = \"app.log\"").unwrap();
+        assert!(parsed.file_output);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_errors_have_line_and_column() {
+        let error = from_toml("level = \"info\"\n\n[file]\nignore = [\"loud\"]", std::iter::empty()).unwrap_err();
+        assert!(error.to_string().contains("line 4, column"), "{error}");
+
+        let error = from_toml("level = \"info\"\ncollapse_timeout = -1.0", std::iter::empty()).unwrap_err();
+        assert!(error.to_string().contains("line 2, column"), "{error}");
+        assert!(error.to_string().contains("non-negative"), "{error}");
+
+        let error = from_toml("\n[terminal]\npath = \"app.log\"", std::iter::empty()).unwrap_err();
+        assert!(error.to_string().contains("line 2, column"), "{error}");
+        assert!(error.to_string().contains("`terminal.path`"), "{error}");
+
+        let error = from_toml("[[sample]]\nlevel = \"trace\"\nrate = { percent = nan }", std::iter::empty()).unwrap_err();
+        assert!(error.to_string().contains("line 3, column"), "{error}");
+        assert!(error.to_string().contains("finite"), "{error}");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_environment_overrides() {
+        let vars = vec![
+            (s!("LOGFATHER_LEVEL"), s!("warn")),
+            (s!("LOGFATHER_FILE__PATH"), s!("/var/log/app.log")),
+            (s!("LOGFATHER_TERMINAL__ENABLED"), s!("false")),
+            (s!("LOGFATHER_LOG_FORMAT"), s!("{level}: {message}")),
+            (s!("PATH"), s!("/usr/bin")),
+            (s!("LOGFATHER_HOME"), s!("/opt/app")),
+            (s!("LOGFATHER_FILE__MODE"), s!("0644")),
+        ];
+        let logger = from_toml("level = \"debug\"\n[file]\ncollapse = true", vars.into_iter()).unwrap();
+
+        assert_eq!(logger.output_level, Level::Warning);
+        assert_eq!(logger.path, Some(PathBuf::from("/var/log/app.log")));
+        assert!(logger.file_output && logger.file_collapse && !logger.terminal_output);
+        assert_eq!(logger.log_format, "{level}: {message}");
+
+        let error = from_toml("", vec![(s!("LOGFATHER_LEVEL"), s!("loud"))].into_iter()).unwrap_err();
+        assert!(error.to_string().contains("LOGFATHER_LEVEL"), "{error}");
+
+        let logger = from_toml("", vec![(s!("LOGFATHER_STYLES__INFO"), s!("[\"bold\"]"))].into_iter()).unwrap();
+        assert_eq!(logger.styles(Level::Info), vec![Style::Bold]);
+    }
+
+    #[test]
+    fn test_unknown_names() {
+        let error = toml::from_str::<Logger>("level = \"verbose\"").unwrap_err().to_string();
+        assert!(error.contains("unknown level `verbose`"), "{error}");
+
+        let error = toml::from_str::<Logger>("[styles]\ninfo = [\"Blink\"]").unwrap_err().to_string();
+        assert!(error.contains("unknown style `Blink`"), "{error}");
+
+        let error = toml::from_str::<Logger>("[[sample]]\nrate = { percent = nan }").unwrap_err().to_string();
+        assert!(error.contains("`sample.rate.percent` must be a finite number, got NaN"), "{error}");
+    }
+}
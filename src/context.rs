@@ -0,0 +1,124 @@
+use std::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+};
+
+thread_local! {
+    static CONTEXT: RefCell<Vec<(u64, String, String)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Guard returned by `push` which removes its value from the context when dropped.
+///
+/// The context is a per-thread stack, so the guard cannot be sent to other threads. Dropping a guard only
+/// removes its own value, even if guards pushed after it are still alive.
+#[must_use = "the value is removed from the context as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct ContextGuard {
+    id: u64,
+    // The context is thread-local, so the guard must stay on the thread that created it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CONTEXT.with(|context| context.borrow_mut().retain(|(id, _, _)| *id != self.id));
+    }
+}
+
+/// Pushes a key-value pair onto the current thread's diagnostic context.
+///
+/// Every message logged on this thread carries the context until the returned guard is dropped. The context
+/// is rendered wherever the `{context}` placeholder appears in the log format, or alongside the message's
+/// structured fields when `Logger::context_fields` is enabled. If the same key is pushed more than once, the
+/// innermost value wins.
+///
+/// # Arguments
+/// * `key` - The name of the context value.
+/// * `value` - The value to attach, captured with its `Display` implementation.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let mut logger = Logger::new();
+/// logger.log_format("[{timestamp} {level} {module_path}] {context} {message}");
+///
+/// fn handle(request_id: u64) {
+///     let _request = logfather::context::push("request_id", request_id);
+///     info!("handling request"); // ... request_id=7 handling request
+/// }
+///
+/// handle(7);
+/// info!("idle"); // The request id is gone once the guard is dropped
+/// ```
+pub fn push(key: &str, value: impl std::fmt::Display) -> ContextGuard {
+    let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+    CONTEXT.with(|context| context.borrow_mut().push((id, key.to_string(), value.to_string())));
+
+    return ContextGuard {
+        id,
+        _not_send: PhantomData,
+    };
+}
+
+/// Returns a copy of the current thread's context with later values overriding earlier ones of the same key.
+pub fn current() -> Vec<(String, String)> {
+    return CONTEXT.with(|context| {
+        let mut current: Vec<(String, String)> = Vec::new();
+        for (_, key, value) in context.borrow().iter() {
+            match current.iter_mut().find(|(k, _)| k == key) {
+                Some(entry) => entry.1 = value.clone(),
+                None => current.push((key.clone(), value.clone())),
+            }
+        }
+        current
+    });
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(values: &[(&str, &str)]) -> Vec<(String, String)> {
+        return values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    }
+
+    #[test]
+    fn test_guards_pop_values() {
+        let _outer = push("request_id", 7);
+        {
+            let _inner = push("user", "ferris");
+            assert_eq!(current(), pairs(&[("request_id", "7"), ("user", "ferris")]));
+        }
+        assert_eq!(current(), pairs(&[("request_id", "7")]));
+    }
+
+    #[test]
+    fn test_inner_values_override() {
+        let _outer = push("stage", "load");
+        let _inner = push("stage", "parse");
+        assert_eq!(current(), pairs(&[("stage", "parse")]));
+    }
+
+    #[test]
+    fn test_guards_drop_out_of_order() {
+        let outer = push("request_id", 7);
+        let inner = push("user", "ferris");
+        drop(outer);
+        assert_eq!(current(), pairs(&[("user", "ferris")]));
+
+        drop(inner);
+        assert!(current().is_empty());
+    }
+
+    #[test]
+    fn test_context_is_thread_local() {
+        let _guard = push("request_id", 7);
+        let other = std::thread::spawn(current).join().unwrap();
+        assert!(other.is_empty());
+    }
+}
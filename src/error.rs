@@ -6,6 +6,7 @@ use crate::logger::*;
 /// - `LoggerAccessError(String)`: Represents an error that occurs when access to the logger is denied or fails.
 /// - `FileAccessError(String)`: Indicates a problem accessing a file needed for logging.
 /// - `IoError(std::io::Error)`: Encompasses general input/output errors that may occur during logging operations.
+/// - `ConfigError(String)`: Indicates an invalid logger configuration, such as an unknown level name.
 ///
 /// # Examples
 /// Handling different kinds of errors:
@@ -28,6 +29,7 @@ pub enum LogfatherError {
     LoggerAccessError(String),
     FileAccessError(String),
     IoError(std::io::Error),
+    ConfigError(String),
 }
 
 impl std::fmt::Display for LogfatherError {
@@ -36,6 +38,7 @@ impl std::fmt::Display for LogfatherError {
             LogfatherError::LoggerAccessError(err) => write!(f, "Failed to access logger: {err}"),
             LogfatherError::FileAccessError(err) => write!(f, "Failed to access file: {err}"),
             LogfatherError::IoError(err) => write!(f, "I/O Error: {err}"),
+            LogfatherError::ConfigError(err) => write!(f, "Invalid configuration: {err}"),
         }
     }
 }
@@ -44,7 +47,7 @@ impl std::fmt::Display for LogfatherError {
 impl std::error::Error for LogfatherError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         return match self {
-            LogfatherError::IoError(err) => err.source(),
+            LogfatherError::IoError(err) => Some(err),
             _ => None,
         };
     }
@@ -72,4 +75,55 @@ impl From<std::io::Error> for LogfatherError {
 }
 
 /// Result type representing `Result<(), LogfatherError>`
-pub type LogfatherResult = Result<(), LogfatherError>;
\ No newline at end of file
+pub type LogfatherResult = Result<(), LogfatherError>;
+
+/// Walks `Error::source()` and collects the message of every cause, outermost first.
+pub(crate) fn causes(error: &dyn std::error::Error) -> Vec<String> {
+    let mut causes = vec![];
+    let mut source = error.source();
+    while let Some(cause) = source {
+        causes.push(cause.to_string());
+        source = cause.source();
+    }
+    return causes;
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[derive(Debug)]
+    struct Wrapped(LogfatherError);
+
+    impl std::fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "failed to write log")
+        }
+    }
+
+    impl std::error::Error for Wrapped {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            return Some(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_io_error_is_source() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let error = LogfatherError::from(io);
+
+        let source = error.source().expect("I/O errors should be the source");
+        assert_eq!(source.to_string(), "no such file");
+    }
+
+    #[test]
+    fn test_causes() {
+        let io = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "permission denied");
+        let error = Wrapped(LogfatherError::from(io));
+
+        assert_eq!(causes(&error), vec!["I/O Error: permission denied", "permission denied"]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,136 @@
+use crate::logger::{self, Level};
+use std::panic::Location;
+
+/// Logs the error of a `Result` at the caller's location and passes the `Result` through unchanged.
+///
+/// Messages are logged with the caller's source file as their module path and target, since the module path of
+/// the caller is only known to macros.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// fn load() -> Result<String, std::io::Error> {
+///     let text = std::fs::read_to_string("config.toml").log_err()?; // [... ERROR src/main.rs] No such file or directory
+///     let _backup = std::fs::read_to_string("backup.toml")
+///         .log_err_at(Level::Warning, "no backup config") // [... WARNING src/main.rs] no backup config: No such file ...
+///         .ok();
+///     return Ok(text);
+/// }
+/// ```
+pub trait LogResultExt {
+    /// Logs the error at `Error`, if there is one.
+    #[track_caller]
+    fn log_err(self) -> Self;
+
+    /// Logs the error at the given level after a context message, if there is one.
+    ///
+    /// # Arguments
+    /// * `level` - The `Level` to log the error at.
+    /// * `context` - A message describing what failed, written before the error.
+    #[track_caller]
+    fn log_err_at(self, level: Level, context: &str) -> Self;
+}
+
+impl<T, E: std::fmt::Display> LogResultExt for Result<T, E> {
+    #[track_caller]
+    fn log_err(self) -> Self {
+        if let Err(e) = &self {
+            log_caller(Level::Error, format_args!("{e}"), Location::caller());
+        }
+        return self;
+    }
+
+    #[track_caller]
+    fn log_err_at(self, level: Level, context: &str) -> Self {
+        if let Err(e) = &self {
+            log_caller(level, format_args!("{context}: {e}"), Location::caller());
+        }
+        return self;
+    }
+}
+
+/// Logs a message when an `Option` is `None` at the caller's location and passes the `Option` through unchanged.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+/// use std::collections::HashMap;
+///
+/// let settings: HashMap<&str, &str> = HashMap::new();
+/// let host = settings.get("host").log_none("missing key `host`").unwrap_or(&"localhost"); // [... WARNING src/main.rs] missing key `host`
+/// ```
+pub trait LogOptionExt {
+    /// Logs a message at `Warning` if the value is `None`.
+    ///
+    /// # Arguments
+    /// * `message` - A message describing what is missing.
+    #[track_caller]
+    fn log_none(self, message: &str) -> Self;
+
+    /// Logs a message at the given level if the value is `None`.
+    ///
+    /// # Arguments
+    /// * `level` - The `Level` to log the message at.
+    /// * `message` - A message describing what is missing.
+    #[track_caller]
+    fn log_none_at(self, level: Level, message: &str) -> Self;
+}
+
+impl<T> LogOptionExt for Option<T> {
+    #[track_caller]
+    fn log_none(self, message: &str) -> Self {
+        return self.log_none_at(Level::Warning, message);
+    }
+
+    #[track_caller]
+    fn log_none_at(self, level: Level, message: &str) -> Self {
+        if self.is_none() {
+            log_caller(level, format_args!("{message}"), Location::caller());
+        }
+        return self;
+    }
+}
+
+fn log_caller(level: Level, args: std::fmt::Arguments, location: &Location) {
+    logger::log_at(level, location.file(), location.file(), args, &[], None, location);
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_pass_through() {
+        let ok: Result<u8, String> = Ok(1);
+        let err: Result<u8, String> = Err(String::from("boom"));
+        assert_eq!(ok.log_err(), Ok(1));
+        assert_eq!(err.log_err_at(Level::Trace, "context"), Err(String::from("boom")));
+
+        assert_eq!(Some(1).log_none("missing"), Some(1));
+        assert_eq!(None::<u8>.log_none_at(Level::Trace, "missing"), None);
+    }
+
+    #[test]
+    fn test_caller_location() {
+        let lines = std::cell::Cell::new((0, 0));
+        let seen = crate::logger::tests::capture(file!(), || {
+            let err: Result<u8, &str> = Err("caller test");
+            _ = err.log_err_at(Level::Trace, "located");
+            let err_line = line!() - 1;
+            _ = None::<u8>.log_none_at(Level::Trace, "located none");
+            lines.set((err_line, line!() - 1));
+        });
+
+        let (err_line, none_line) = lines.get();
+        let seen: Vec<&String> = seen.iter().filter(|line| line.contains("located")).collect();
+        assert_eq!(seen, vec![
+            &format!("{0} {0}:{1} located: caller test", file!(), err_line),
+            &format!("{0} {0}:{1} located none", file!(), none_line),
+        ]);
+    }
+}
@@ -0,0 +1,241 @@
+/// A structured field value captured by the logging macros.
+///
+/// # Variants
+///
+/// - `Display`: The value is rendered with its `Display` implementation (`key = %value` or `key = value`).
+/// - `Debug`: The value is rendered with its `Debug` implementation (`key = ?value`).
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let path = "/index.html";
+/// let headers = vec!["Accept", "Host"];
+/// info!(user_id = 42, path = %path, headers = ?headers; "request served");
+/// ```
+#[derive(Clone, Copy)]
+pub enum Value<'a> {
+    Display(&'a dyn std::fmt::Display),
+    Debug(&'a dyn std::fmt::Debug),
+}
+
+impl std::fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Value::Display(value) => write!(f, "{}", value),
+            Value::Debug(value) => write!(f, "{:?}", value),
+        };
+    }
+}
+
+impl std::fmt::Debug for Value<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Value::Display(value) => write!(f, "Display({})", value),
+            Value::Debug(value) => write!(f, "Debug({:?})", value),
+        };
+    }
+}
+
+/// A structured key-value pair attached to a log message.
+pub type Field<'a> = (&'a str, Value<'a>);
+
+/// Renders fields as space separated `key=value` pairs.
+///
+/// Values containing whitespace, `=` or `"` are quoted so the output stays parseable.
+pub(crate) fn render(fields: &[Field]) -> String {
+    let pairs: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| {
+            let value = value.to_string();
+            if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '=' || c == '"') {
+                format!("{}={:?}", key, value)
+            } else {
+                format!("{}={}", key, value)
+            }
+        })
+        .collect();
+    return pairs.join(" ");
+}
+
+/// Wraps the value given to `err:` so the logging macros accept errors as well as values dereferencing to one.
+///
+/// The traits below are tried in order by method resolution - the fewer references the implementing type has to
+/// go through, the earlier it is tried - so any `Error` is used as is, while `Box<dyn Error>`, `anyhow::Error` and
+/// other types implementing `AsRef<dyn Error>` are dereferenced first.
+#[doc(hidden)]
+pub struct ErrorRef<'a, T: ?Sized>(pub &'a T);
+
+#[doc(hidden)]
+pub trait ViaError<'a> {
+    fn as_dyn_error(&self) -> &'a (dyn std::error::Error + 'a);
+}
+
+impl<'a, T: std::error::Error + 'a> ViaError<'a> for &ErrorRef<'a, T> {
+    fn as_dyn_error(&self) -> &'a (dyn std::error::Error + 'a) {
+        return self.0;
+    }
+}
+
+#[doc(hidden)]
+pub trait ViaAsRef<'a> {
+    fn as_dyn_error(&self) -> &'a (dyn std::error::Error + 'a);
+}
+
+impl<'a, T: AsRef<dyn std::error::Error> + ?Sized> ViaAsRef<'a> for &&ErrorRef<'a, T> {
+    fn as_dyn_error(&self) -> &'a (dyn std::error::Error + 'a) {
+        return AsRef::<dyn std::error::Error>::as_ref(self.0);
+    }
+}
+
+#[doc(hidden)]
+pub trait ViaAsRefSendSync<'a> {
+    fn as_dyn_error(&self) -> &'a (dyn std::error::Error + 'a);
+}
+
+impl<'a, T: AsRef<dyn std::error::Error + Send + Sync> + ?Sized> ViaAsRefSendSync<'a> for ErrorRef<'a, T> {
+    fn as_dyn_error(&self) -> &'a (dyn std::error::Error + 'a) {
+        return AsRef::<dyn std::error::Error + Send + Sync>::as_ref(self.0);
+    }
+}
+
+/// Builds the field array for the logging macros from `key = value` pairs.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fields {
+    ([$($acc:tt)*]) => {
+        [$($acc)*]
+    };
+    ([$($acc:tt)*] $key:ident = % $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__fields!([$($acc)* (stringify!($key), $crate::kv::Value::Display(&$value)),] $($($rest)*)?)
+    };
+    ([$($acc:tt)*] $key:ident = ? $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__fields!([$($acc)* (stringify!($key), $crate::kv::Value::Debug(&$value)),] $($($rest)*)?)
+    };
+    ([$($acc:tt)*] $key:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__fields!([$($acc)* (stringify!($key), $crate::kv::Value::Display(&$value)),] $($($rest)*)?)
+    };
+}
+
+/// Splits the arguments of the logging macros into a logger, a target, an error, fields and format arguments and calls the log function.
+///
+/// The first argument selects the family of log functions - `log` for the plain macros and `result_log` for the `r_` macros.
+/// Messages go to the global logger unless a `logger:` is given, and their target defaults to the logger's.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    (@call log, [], $level:expr, $target:expr, $args:expr, $fields:expr $(, $err:expr)?) => {
+        $crate::logger::log_callsite($level, module_path!(), $target, $args, $fields, $crate::__log!(@error $($err)?))
+    };
+    (@call log, [$logger:expr], $level:expr, $target:expr, $args:expr, $fields:expr $(, $err:expr)?) => {
+        $logger.log_callsite($level, module_path!(), $target, $args, $fields, $crate::__log!(@error $($err)?))
+    };
+    (@call result_log, [], $level:expr, $target:expr, $args:expr, $fields:expr $(, $err:expr)?) => {
+        $crate::logger::result_log_callsite($level, module_path!(), $target, $args, $fields, $crate::__log!(@error $($err)?))
+    };
+    (@call result_log, [$logger:expr], $level:expr, $target:expr, $args:expr, $fields:expr $(, $err:expr)?) => {
+        $logger.result_log_callsite($level, module_path!(), $target, $args, $fields, $crate::__log!(@error $($err)?))
+    };
+    (@error $err:expr) => {
+        ::std::option::Option::Some({
+            #[allow(unused_imports)]
+            use $crate::kv::{ViaAsRef as _, ViaAsRefSendSync as _, ViaError as _};
+            (&&$crate::kv::ErrorRef($err)).as_dyn_error()
+        })
+    };
+    (@error) => {
+        ::std::option::Option::None
+    };
+    (@split $family:ident, $logger:tt, $level:expr, $target:expr, [$($err:expr)?], [$($fields:tt)*] ; $($arg:tt)+) => {
+        $crate::__log!(@call $family, $logger, $level, $target, format_args!($($arg)+), &$crate::__fields!([] $($fields)*) $(, &$err)?)
+    };
+    (@split $family:ident, $logger:tt, $level:expr, $target:expr, [$($err:expr)?], [$($fields:tt)*]) => {
+        $crate::__log!(@call $family, $logger, $level, $target, format_args!(""), &$crate::__fields!([] $($fields)*) $(, &$err)?)
+    };
+    (@split $family:ident, $logger:tt, $level:expr, $target:expr, [$($err:expr)?], [$($fields:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::__log!(@split $family, $logger, $level, $target, [$($err)?], [$($fields)* $next] $($rest)*)
+    };
+    (@target $family:ident, $logger:tt, $level:expr, $target:expr, err: $err:expr ; $($arg:tt)+) => {
+        $crate::__log!(@call $family, $logger, $level, $target, format_args!($($arg)+), &[], &$err)
+    };
+    (@target $family:ident, $logger:tt, $level:expr, $target:expr, err: $err:expr , $($rest:tt)+) => {
+        $crate::__log!(@split $family, $logger, $level, $target, [$err], [] $($rest)+)
+    };
+    (@target $family:ident, $logger:tt, $level:expr, $target:expr, err: $err:expr) => {
+        $crate::__log!(@call $family, $logger, $level, $target, format_args!(""), &[], &$err)
+    };
+    (@target $family:ident, $logger:tt, $level:expr, $target:expr, $key:ident = $($rest:tt)+) => {
+        $crate::__log!(@split $family, $logger, $level, $target, [], [$key =] $($rest)+)
+    };
+    (@target $family:ident, $logger:tt, $level:expr, $target:expr, $($arg:tt)+) => {
+        $crate::__log!(@call $family, $logger, $level, $target, format_args!($($arg)+), &[])
+    };
+    (@logger $family:ident, $logger:tt, $level:expr, target: $target:expr, $($rest:tt)+) => {
+        $crate::__log!(@target $family, $logger, $level, ::std::option::Option::Some($target), $($rest)+)
+    };
+    (@logger $family:ident, $logger:tt, $level:expr, $($rest:tt)+) => {
+        $crate::__log!(@target $family, $logger, $level, ::std::option::Option::None, $($rest)+)
+    };
+    ($family:ident, $level:expr, logger: $logger:expr, $($rest:tt)+) => {
+        $crate::__log!(@logger $family, [$logger], $level, $($rest)+)
+    };
+    ($family:ident, $level:expr, $($rest:tt)+) => {
+        $crate::__log!(@logger $family, [], $level, $($rest)+)
+    };
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let path = "/index.html";
+        let tags = vec!["a", "b"];
+        let fields: [Field; 4] = [
+            ("user_id", Value::Display(&42)),
+            ("path", Value::Display(&path)),
+            ("tags", Value::Debug(&tags)),
+            ("agent", Value::Display(&"curl 8.0")),
+        ];
+
+        assert_eq!(
+            render(&fields),
+            r#"user_id=42 path=/index.html tags="[\"a\", \"b\"]" agent="curl 8.0""#
+        );
+    }
+
+    #[test]
+    fn test_error_ref() {
+        #[derive(Debug)]
+        struct Context(Box<dyn std::error::Error + Send + Sync>);
+
+        impl AsRef<dyn std::error::Error> for Context {
+            fn as_ref(&self) -> &(dyn std::error::Error + 'static) {
+                return &*self.0;
+            }
+        }
+
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let boxed: Box<dyn std::error::Error> = Box::new(std::io::Error::new(std::io::ErrorKind::NotFound, "boxed"));
+        let shared: Box<dyn std::error::Error + Send + Sync> = "shared".into();
+        let context = Context("wrapped".into());
+
+        let render = |error: Option<&dyn std::error::Error>| error.unwrap().to_string();
+        assert_eq!(render(crate::__log!(@error &io)), "no such file");
+        assert_eq!(render(crate::__log!(@error &boxed)), "boxed");
+        assert_eq!(render(crate::__log!(@error &shared)), "shared");
+        assert_eq!(render(crate::__log!(@error &context)), "wrapped");
+    }
+
+    #[test]
+    fn test_fields_macro() {
+        let name = "ferris";
+        let fields: [Field; 3] = crate::__fields!([] id = 7, name = %name, list = ?[1, 2]);
+
+        assert_eq!(render(&fields), r#"id=7 name=ferris list="[1, 2]""#);
+    }
+}
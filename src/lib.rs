@@ -7,19 +7,47 @@
 //! - Easy to set up and use
 //! - Supports logging to both the terminal and log files
 //! - Customizable log message format
-//! - Configurable log levels (Info, Debug, Warning, Error, Critical, and Diagnostic)
+//! - Configurable log levels (Trace, Debug, Info, Warning, Error, Critical, and Diagnostic), plus `NOTICE`, `AUDIT` and `FATAL`
 //! - Configurable level display including colors, highlights, and styles
+//! - User-defined levels with their own severity, name and styles (`Level::register`)
 //! - Optional result (prepend `r_`) macros for managed errors
+//! - Structured key-value fields, `target:` overrides and errors with their source chain (`kv`, `info!`, `error!`)
+//! - Thread-local diagnostic context attached to every log line (`context`)
+//! - Nested spans logging their entry, exit and elapsed time (`span`)
+//! - Optional backtraces for severe messages (`Logger::backtrace`)
+//! - Panic hook logging panics through every output (`install_panic_hook`)
+//! - Capture of child process output, line by line (`process`)
+//! - `io::Write` adapter logging each written line (`writer`)
+//! - `Result` and `Option` extensions logging failures at the caller's location (`ext`)
+//! - Per-callsite rate limiting (`rate_limit`)
+//! - Collapsing of consecutive duplicate messages, per output (`Logger::terminal_collapse`, `Logger::file_collapse`)
+//! - Sampling of high-volume levels and modules (`sample`)
 //! - Thread-safe
-//! 
+//! - Cheap `log_enabled!` checks for skipping expensive log arguments
+//! - Standalone and hierarchical named loggers alongside the global one (`Logger::standalone`, `named`)
+//! - Custom filters and formatters working on a public `Record` (`record`)
+//! 
+//! ### Cargo features
+//! - `log` - routes the `log` crate facade, including your dependencies' messages, through logfather (`log_facade`)
+//! - `tracing` - a `tracing_subscriber` layer forwarding events with their spans and fields (`tracing_layer`)
+//! - `serde` - `Serialize` and `Deserialize` for the logger configuration
+//! - `toml` - `Logger::from_file` and `watch_file`, with `LOGFATHER_*` environment variable overrides and hot reload
+//! - `instrument` - the `#[instrument]` attribute logging function entry, exit, arguments and elapsed time
+//! - `max_level_*` and `release_max_level_*` - compile levels out entirely, in debug and release builds (`STATIC_OUTPUT_LEVEL`)
+//!
 //! ## Getting Started
 //! To start using Logfather, add the following to your `Cargo.toml`:
 //! ```toml
 //! [dependencies]
-//! logfather = "0.2.6"
+//! logfather = "0.3.0"
 //! - Check out [crates.io](https://crates.io/crates/logfather)
 //! ```
-//! - Minimum supported Rust version: `1.61.0`
+//! - Minimum supported Rust version: `1.65.0`
+//!
+//! ### Upgrading from 0.2
+//! `Level` gained a `Custom(u8)` variant for user-defined levels, so exhaustive `match`es on it need a wildcard arm and
+//! `Level::Error as u8` no longer compiles - use `Level::Error.severity()`. The built-in severities are also spaced out
+//! now (Trace 0, Debug 20, Info 40, Warning 60, Error 80, Critical 100) to leave room for custom levels between them.
 //! 
 //! ## Usage
 //! Macros:
@@ -96,17 +124,67 @@
 //! ```
 
 
+// Lets the paths generated by `#[instrument]` resolve inside this crate's own tests
+#[cfg(all(test, feature = "instrument"))]
+extern crate self as logfather;
+
 pub mod logger;
+pub mod context;
 pub mod error;
+pub mod kv;
+mod collapse;
+pub mod rate_limit;
+pub mod sample;
+pub mod span;
+pub mod record;
+pub mod named;
+mod panic;
+pub mod process;
+pub mod writer;
+pub mod ext;
+#[cfg(feature = "log")]
+pub mod log_facade;
+#[cfg(feature = "tracing")]
+pub mod tracing_layer;
+#[cfg(feature = "serde")]
+mod config;
+#[cfg(feature = "toml")]
+pub mod watch;
 
 pub use dekor::Style;
 pub use logger::Logger;
 pub use logger::Level;
+pub use logger::STATIC_OUTPUT_LEVEL;
 pub use logger::TimeZone;
+pub use rate_limit::RateLimit;
+pub use rate_limit::RateLimitKey;
+pub use sample::SampleRate;
+pub use sample::SampleRule;
+pub use record::Record;
+pub use named::get;
+pub use named::NamedLogger;
 pub use error::LogfatherError;
 pub use error::LogfatherResult;
 pub use logger::log;
+pub use logger::log_fields;
+pub use logger::log_error;
+pub use logger::log_record;
+pub use logger::flush;
+pub use panic::install_panic_hook;
+pub use process::ChildOutput;
+pub use writer::LogWriter;
+pub use ext::LogResultExt;
+pub use ext::LogOptionExt;
+#[cfg(feature = "instrument")]
+pub use logfather_macros::instrument;
 pub use logger::result_log;
+pub use logger::result_log_fields;
+pub use logger::result_log_error;
+pub use logger::result_log_record;
+#[cfg(feature = "toml")]
+pub use watch::watch_file;
+#[cfg(feature = "toml")]
+pub use watch::ConfigWatcher;
 
 #[doc = include_str!("../README.md")]
 #[cfg(doctest)]
@@ -0,0 +1,111 @@
+use crate::{
+    error::*,
+    logger::{self, Level},
+    record::Record,
+};
+
+/// Backend for the `log` crate facade which forwards every message to the global logfather logger.
+///
+/// Messages keep the target, module path and source location reported by `log` and go through logfather's
+/// level filters, sampling, rate limiting and outputs like any other message. Install it with `init`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogBackend;
+
+impl ::log::Log for LogBackend {
+    fn enabled(&self, metadata: &::log::Metadata) -> bool {
+        return logger::enabled(&level(metadata.level()));
+    }
+
+    fn log(&self, record: &::log::Record) {
+        let level = level(record.level());
+        if !logger::enabled(&level) {
+            return;
+        }
+
+        let module_path = record.module_path().unwrap_or(record.target());
+        let mut forwarded = Record::new(level, module_path, *record.args()).with_target(record.target());
+        if let (Some(file), Some(line)) = (record.file(), record.line()) {
+            forwarded = forwarded.with_location(file, line);
+        }
+        logger::log_record(forwarded);
+    }
+
+    fn flush(&self) {
+        if let Err(e) = logger::flush() {
+            eprintln!("{e}");
+        }
+    }
+}
+
+/// Maps a `log` crate level to the matching logfather `Level`.
+pub fn level(level: ::log::Level) -> Level {
+    return match level {
+        ::log::Level::Error => Level::Error,
+        ::log::Level::Warn => Level::Warning,
+        ::log::Level::Info => Level::Info,
+        ::log::Level::Debug => Level::Debug,
+        ::log::Level::Trace => Level::Trace,
+    };
+}
+
+/// Installs logfather as the global logger of the `log` crate.
+///
+/// Every `log::info!` and friends - including the ones in dependencies - is then written by the global logfather
+/// logger. Configure it with `Logger` as usual, before or after calling this.
+///
+/// # Returns
+/// Returns a `LogfatherError::LoggerAccessError` if another `log` backend is already installed.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// logfather::log_facade::init().expect("Another log backend is installed");
+/// let mut logger = Logger::new();
+/// logger.level(Level::Info);
+///
+/// log::info!(target: "hyper", "listening on {}", 8080); // [... INFO hyper] listening on 8080
+/// ```
+pub fn init() -> LogfatherResult {
+    ::log::set_boxed_logger(Box::new(LogBackend))
+        .map_err(|e| LogfatherError::LoggerAccessError(e.to_string()))?;
+    // Filtering is left to logfather, whose level can change at runtime
+    ::log::set_max_level(::log::LevelFilter::Trace);
+    return Ok(());
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_mapping() {
+        assert_eq!(level(::log::Level::Error), Level::Error);
+        assert_eq!(level(::log::Level::Warn), Level::Warning);
+        assert_eq!(level(::log::Level::Info), Level::Info);
+        assert_eq!(level(::log::Level::Debug), Level::Debug);
+        assert_eq!(level(::log::Level::Trace), Level::Trace);
+    }
+
+    #[test]
+    fn test_records_keep_their_origin() {
+        let seen = crate::logger::tests::capture("hyper", || {
+            ::log::Log::log(
+                &LogBackend,
+                &::log::Record::builder()
+                    .level(::log::Level::Warn)
+                    .target("hyper::proto")
+                    .module_path(Some("hyper::proto::h1"))
+                    .file(Some("src/proto/h1.rs"))
+                    .line(Some(88))
+                    .args(format_args!("connection reset"))
+                    .build(),
+            );
+        });
+
+        assert_eq!(seen, vec!["hyper::proto src/proto/h1.rs:88 connection reset"]);
+    }
+}
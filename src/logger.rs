@@ -1,9 +1,27 @@
-use crate::error::*;
-use chrono::{prelude::Local, Utc};
+use crate::error::{self, *};
+use crate::context;
+use crate::kv::{self, Field, Value};
+use crate::named;
+use crate::collapse::{self, Collapser, Observed, Output};
+use crate::rate_limit::{self, Limiter, RateLimit, Verdict};
+use crate::record::{Filter, Formatter, Record};
+use crate::sample::{self, SampleRule};
+use crate::span;
+use chrono::prelude::Local;
 use dekor::*;
 use lazy_static::lazy_static;
 use simplicio::*;
-use std::{io::Write, path::PathBuf};
+use std::{
+    backtrace::Backtrace,
+    io::Write,
+    panic::Location,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, AtomicU8, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
 // TODO:
 // 1. Implement advanced error handling for file operations
@@ -17,8 +35,19 @@ use std::{io::Write, path::PathBuf};
 
 lazy_static! {
     static ref LOGGER: std::sync::RwLock<Logger> = std::sync::RwLock::new(Logger::new());
+    // Labels and default styles of the custom levels, keyed by severity
+    static ref LEVELS: std::sync::RwLock<std::collections::HashMap<u8, (String, Vec<Style>)>> =
+        std::sync::RwLock::new(map!(
+            (Level::NOTICE.severity())  => (s!("NOTICE"), vec![Style::FGCyan]),
+            (Level::AUDIT.severity())   => (s!("AUDIT"), vec![Style::Bold, Style::FGYellow]),
+            (Level::FATAL.severity())   => (s!("FATAL"), vec![Style::Bold, Style::BGRed]),
+        ));
 }
 
+// Mirrors of the global logger's `output_level` and `ignore` list, checked before the logger is locked
+static OUTPUT_LEVEL: AtomicU8 = AtomicU8::new(0);
+static IGNORED: [AtomicU64; 4] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+
 /// Replaces the current global logger instance with a new one.
 ///
 /// This function allows updating the global logger configuration at runtime. It should be used
@@ -40,8 +69,128 @@ lazy_static! {
 /// logfather::logger::set_logger(&custom_logger); // Apply the custom logger globally
 /// ```
 pub fn set_logger(new_logger: &Logger) {
+    update_logger(|logger| *logger = new_logger.clone());
+}
+
+/// Changes the global logger in place, under a single write lock.
+///
+/// # Panics
+/// Panics if the global logger lock cannot be acquired.
+pub(crate) fn update_logger(update: impl FnOnce(&mut Logger)) {
     let mut logger = LOGGER.write().expect("Could not access the logger");
-    *logger = new_logger.clone();
+    update(&mut logger);
+
+    // Updated while the write lock is held so the mirrors never disagree with the logger for long
+    OUTPUT_LEVEL.store(logger.output_level.severity(), Ordering::Relaxed);
+    for (ignored, mask) in IGNORED.iter().zip(ignore_mask(&logger.ignore)) {
+        ignored.store(mask, Ordering::Relaxed);
+    }
+    named::invalidate();
+}
+
+/// Returns a copy of the global logger.
+pub(crate) fn global() -> Result<Logger, LogfatherError> {
+    return Ok(LOGGER.read().map_err(LogfatherError::from)?.clone());
+}
+
+/// Returns a copy of the global logger, or `None` when its lock is held for writing or poisoned.
+pub(crate) fn try_global() -> Option<Logger> {
+    return LOGGER.try_read().ok().map(|logger| logger.clone());
+}
+
+/// The minimum level compiled into the logging macros, selected with the `max_level_*` and `release_max_level_*` cargo features.
+///
+/// Macros for levels below this one expand to no-ops which the compiler removes entirely, regardless of the logger's
+/// runtime configuration. The `release_max_level_*` features only apply to builds without `debug_assertions` and take
+/// precedence over `max_level_*` there. When several features of the same kind are enabled, the most restrictive wins.
+///
+/// # Features
+/// - `max_level_off`, `release_max_level_off`: Compile out every level.
+/// - `max_level_critical`, `release_max_level_critical`: Keep `Critical` and above.
+/// - `max_level_error`, `release_max_level_error`: Keep `Error` and above.
+/// - `max_level_warn`, `release_max_level_warn`: Keep `Warning` and above.
+/// - `max_level_info`, `release_max_level_info`: Keep `Info` and above.
+/// - `max_level_debug`, `release_max_level_debug`: Keep `Debug` and above.
+/// - `max_level_trace`, `release_max_level_trace`: Keep every level (default).
+///
+/// # Examples
+/// ```toml
+/// [dependencies]
+/// logfather = { version = "0.3.0", features = ["release_max_level_warn"] }
+/// ```
+pub const STATIC_OUTPUT_LEVEL: Level = static_output_level();
+
+const fn static_output_level() -> Level {
+    if cfg!(not(debug_assertions)) {
+        if cfg!(feature = "release_max_level_off") {
+            return Level::None;
+        } else if cfg!(feature = "release_max_level_critical") {
+            return Level::Critical;
+        } else if cfg!(feature = "release_max_level_error") {
+            return Level::Error;
+        } else if cfg!(feature = "release_max_level_warn") {
+            return Level::Warning;
+        } else if cfg!(feature = "release_max_level_info") {
+            return Level::Info;
+        } else if cfg!(feature = "release_max_level_debug") {
+            return Level::Debug;
+        } else if cfg!(feature = "release_max_level_trace") {
+            return Level::Trace;
+        }
+    }
+
+    if cfg!(feature = "max_level_off") {
+        return Level::None;
+    } else if cfg!(feature = "max_level_critical") {
+        return Level::Critical;
+    } else if cfg!(feature = "max_level_error") {
+        return Level::Error;
+    } else if cfg!(feature = "max_level_warn") {
+        return Level::Warning;
+    } else if cfg!(feature = "max_level_info") {
+        return Level::Info;
+    } else if cfg!(feature = "max_level_debug") {
+        return Level::Debug;
+    }
+    return Level::Trace;
+}
+
+/// Checks whether a message of the given level would pass the global logger's level filters.
+///
+/// This only reads atomics mirroring the logger's `output_level` and `ignore` list, so it is cheap enough to call
+/// before preparing expensive log arguments. Prefer the `log_enabled!` macro, which also respects the levels
+/// compiled out by cargo features.
+///
+/// # Arguments
+/// * `level` - The severity level to check.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// if logfather::logger::enabled(&Level::Trace) {
+///     trace!("Expensive state: {:?}", vec![1, 2, 3]);
+/// }
+/// ```
+pub fn enabled(level: &Level) -> bool {
+    let severity = level.severity();
+    if severity < OUTPUT_LEVEL.load(Ordering::Relaxed) {
+        return false;
+    }
+
+    let ignored = IGNORED[(severity / 64) as usize].load(Ordering::Relaxed);
+    return ignored & (1 << (severity % 64)) == 0;
+}
+
+/// Builds a 256-bit mask with the bit for each ignored level's severity set.
+fn ignore_mask(ignore: &[Level]) -> [u64; 4] {
+    let mut mask = [0; 4];
+    for level in ignore {
+        let severity = level.severity();
+        mask[(severity / 64) as usize] |= 1 << (severity % 64);
+    }
+    return mask;
 }
 
 /// `Logger` is a struct that encapsulates the configuration for the logging system.
@@ -54,9 +203,21 @@ pub fn set_logger(new_logger: &Logger) {
 /// - `ignore`: Global list of log levels to ignore - more granular than output_level.
 /// - `file_ignore`: List of log levels that file output ignores.
 /// - `terminal_ignore`: List of log levels that terminal output ignores.
-/// - `log_format`: The format string for log messages. Placeholders like `{timestamp}`, `{module_path}`, `{level}`, and `{message}` will be replaced with actual values.
+/// - `log_format`: The format string for log messages. Placeholders like `{timestamp}`, `{module_path}`, `{target}`, `{level}`, `{sample_rate}`, `{fields}`, `{context}`, `{span}`, and `{message}` will be replaced with actual values.
 /// - `timestampt_format`: The format string for time display. Placeholders like `%y`, `%m`, `%d`, `%H`, `%M`, and `%S` will be replaced with actual values.
 /// - `styles`: HashMap relating a `Level` to a `TextStyle` for terminal output customization.
+/// - `rate_limit`: Optional token bucket limiting how often a single callsite may log.
+/// - `file_collapse`: Boolean flag to collapse consecutive duplicate messages written to the file.
+/// - `terminal_collapse`: Boolean flag to collapse consecutive duplicate messages written to the terminal.
+/// - `collapse_timeout`: Maximum duration of a run of collapsed messages before its summary is written.
+/// - `sampling`: List of sampling rules keeping only a portion of the matching messages.
+/// - `context_fields`: Boolean flag to render the thread's diagnostic context as structured fields.
+/// - `backtrace_level`: Minimum level of log messages which capture a backtrace - `Level::None` disables capturing.
+/// - `filters`: List of predicates a `Record` must pass to be logged.
+/// - `formatter`: Optional function rendering a `Record` in place of `log_format`.
+/// - `standalone`: Boolean flag keeping the setters from replacing the global logger.
+/// - `collapser`: Runs of repeated messages being collapsed - shared by clones of the logger.
+/// - `limiter`: Token buckets of the rate limit - shared by clones of the logger.
 ///
 /// # Examples
 ///
@@ -76,6 +237,11 @@ pub fn set_logger(new_logger: &Logger) {
 /// logger.log_format("[{timestamp} {level}] {message}"); // Set a custom format for log messages
 /// logger.timestamp_format("%Y-%m-%d %H:%M:%S"); // Set a custom format for timestamps
 /// logger.add_style(Level::Info, Style::Underline); // Set the style for INFO to Underlined in terminal output
+/// logger.rate_limit(RateLimit::new(10, 100)); // Allow 10 messages per second per callsite with bursts of 100
+/// logger.file_collapse(true); // Collapse consecutive duplicate messages written to the file
+/// logger.collapse_timeout(std::time::Duration::from_secs(60)); // Summarize runs of duplicates at least once a minute
+/// logger.sample(SampleRule::new(SampleRate::OneIn(10)).level(Level::Trace)); // Keep 1 in 10 trace messages
+/// logger.backtrace(Level::Error); // Capture backtraces for Error and Critical messages
 /// ```
 #[derive(Clone, Debug)]
 pub struct Logger {
@@ -90,6 +256,18 @@ pub struct Logger {
     pub(crate) timezone: TimeZone,
     pub(crate) timestamp_format: String,
     pub(crate) styles: std::collections::HashMap<Level, Vec<Style>>,
+    pub(crate) rate_limit: Option<RateLimit>,
+    pub(crate) file_collapse: bool,
+    pub(crate) terminal_collapse: bool,
+    pub(crate) collapse_timeout: Duration,
+    pub(crate) sampling: Vec<SampleRule>,
+    pub(crate) context_fields: bool,
+    pub(crate) backtrace_level: Level,
+    pub(crate) filters: Vec<Filter>,
+    pub(crate) formatter: Option<Formatter>,
+    pub(crate) standalone: bool,
+    pub(crate) collapser: Arc<Mutex<Collapser>>,
+    pub(crate) limiter: Arc<Mutex<Limiter>>,
 }
 
 impl Default for Logger {
@@ -131,15 +309,54 @@ impl Logger {
             timezone: TimeZone::Local,
             timestamp_format: s!("%Y-%m-%d %H:%M:%S"),
             styles: map!(
-                Level::Trace        => vec![Style::FGPurple],
-                Level::Debug        => vec![Style::FGBlue],
-                Level::Info         => vec![Style::FGGreen],
-                Level::Warning      => vec![Style::FGYellow],
-                Level::Error        => vec![Style::FGRed],
-                Level::Critical     => vec![Style::Bold, Style::FGRed],
-                Level::Diagnostic   => vec![Style::Bold, Style::FGCyan],
-                Level::None         => vec![],
+                Level::Trace        => Level::Trace.default_styles(),
+                Level::Debug        => Level::Debug.default_styles(),
+                Level::Info         => Level::Info.default_styles(),
+                Level::Warning      => Level::Warning.default_styles(),
+                Level::Error        => Level::Error.default_styles(),
+                Level::Critical     => Level::Critical.default_styles(),
+                Level::Diagnostic   => Level::Diagnostic.default_styles(),
+                Level::None         => Level::None.default_styles(),
             ),
+            rate_limit: None,
+            file_collapse: false,
+            terminal_collapse: false,
+            collapse_timeout: Duration::from_secs(30),
+            sampling: vec![],
+            context_fields: false,
+            backtrace_level: Level::None,
+            filters: vec![],
+            formatter: None,
+            standalone: false,
+            collapser: Arc::new(Mutex::new(Collapser::default())),
+            limiter: Arc::new(Mutex::new(Limiter::default())),
+        }
+    }
+
+    /// Constructs a new `Logger` instance with default settings which is never installed as the global logger.
+    ///
+    /// Setters on a standalone logger only change the instance itself. Log through it with `Logger::log` or the
+    /// `logger:` form of the logging macros, so a library can own its logger without clobbering the application's.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let logger = Logger::standalone().level(Level::Warning); // The global logger is left untouched
+    /// warning!(logger: &logger, "disk almost full");
+    /// ```
+    pub fn standalone() -> Self {
+        return Self {
+            standalone: true,
+            ..Self::new()
+        };
+    }
+
+    /// Installs the configuration as the global logger unless the logger is standalone.
+    fn apply(&self) {
+        if !self.standalone {
+            set_logger(self);
         }
     }
 
@@ -160,7 +377,7 @@ impl Logger {
     /// ```
     pub fn path(&mut self, path: &str) -> Self {
         self.path = Some(PathBuf::from(path));
-        set_logger(self);
+        self.apply();
         return self.to_owned();
     }
 
@@ -179,7 +396,7 @@ impl Logger {
     /// ```
     pub fn terminal(&mut self, value: bool) -> Self {
         self.terminal_output = value;
-        set_logger(self);
+        self.apply();
         return self.to_owned();
     }
 
@@ -201,7 +418,7 @@ impl Logger {
     /// ```
     pub fn file(&mut self, value: bool) -> Self {
         self.file_output = value;
-        set_logger(self);
+        self.apply();
         return self.to_owned();
     }
 
@@ -222,7 +439,7 @@ impl Logger {
     /// ```
     pub fn level(&mut self, level: Level) -> Self {
         self.output_level = level;
-        set_logger(self);
+        self.apply();
         return self.to_owned();
     }
 
@@ -243,7 +460,7 @@ impl Logger {
     /// ```
     pub fn ignore(&mut self, level: Level) -> Self {
         self.ignore.push(level);
-        set_logger(self);
+        self.apply();
         return self.to_owned();
     }
 
@@ -264,7 +481,7 @@ impl Logger {
     /// ```
     pub fn file_ignore(&mut self, level: Level) -> Self {
         self.file_ignore.push(level);
-        set_logger(self);
+        self.apply();
         return self.to_owned();
     }
 
@@ -285,13 +502,18 @@ impl Logger {
     /// ```
     pub fn terminal_ignore(&mut self, level: Level) -> Self {
         self.terminal_ignore.push(level);
-        set_logger(self);
+        self.apply();
         return self.to_owned();
     }
 
     /// Sets the format string for log messages.
     ///
     /// The format string can contain placeholders like `{timestamp}`, `{module_path}`, `{level}`, and `{message}` which will be replaced with actual values during logging.
+    /// `{module_path}` and `{target}` are both replaced with the message's target - its module path unless overridden with `target:`.
+    /// `{sample_rate}` is replaced with the fraction of messages kept by sampling, from `0` to `1`, so counts can be scaled downstream.
+    /// `{fields}` is replaced with the message's structured fields as `key=value` pairs - without it, fields trail the formatted line.
+    /// `{context}` is replaced with the thread's diagnostic context (see `logfather::context::push`) as `key=value` pairs.
+    /// `{span}` is replaced with the path of the thread's open spans (see `span!`), e.g. `startup/load_config`.
     ///
     /// # Arguments
     /// * `format` - A string slice representing the log message format.
@@ -306,7 +528,7 @@ impl Logger {
     /// ```
     pub fn log_format(&mut self, format: &str) -> Self {
         self.log_format = s!(format);
-        set_logger(self);
+        self.apply();
         return self.to_owned();
     }
 
@@ -325,7 +547,7 @@ impl Logger {
     /// ```
     pub fn timezone(&mut self, timezone: TimeZone) -> Self {
         self.timezone = timezone;
-        set_logger(self);
+        self.apply();
         return self.to_owned();
     }
 
@@ -348,7 +570,7 @@ impl Logger {
     /// ```
     pub fn timestamp_format(&mut self, format: &str) -> Self {
         self.timestamp_format = s!(format);
-        set_logger(self);
+        self.apply();
         return self.to_owned();
     }
 
@@ -386,8 +608,7 @@ impl Logger {
     /// * `level` - The log level to modify.
     /// * `style` - The style to add to the list of styles for the specified level.
     ///
-    /// # Panics
-    /// Panics if the log level does not already have an associated list of styles.
+    /// Custom levels start from the styles they were registered with.
     ///
     /// # Returns
     /// Returns the modified `Logger` instance to allow for method chaining.
@@ -400,9 +621,9 @@ impl Logger {
     /// logger.add_style(Level::Error, Style::FGRed); // Add red color to ERROR level messages
     /// ```
     pub fn add_style(&mut self, level: Level, style: Style) -> Self {
-        let styles = self.styles.get_mut(&level).expect("Magic has occured");
-        styles.push(style);
-        set_logger(self);
+        let default = level.default_styles();
+        self.styles.entry(level).or_insert(default).push(style);
+        self.apply();
         return self.to_owned();
     }
 
@@ -421,9 +642,9 @@ impl Logger {
     /// logger.remove_style(Level::Info, Style::BGBlue);
     /// ```
     pub fn remove_style(&mut self, level: Level, style: Style) -> Self {
-        let styles = self.styles.get_mut(&level).expect("Magic has occured");
-        styles.retain(|s| *s != style);
-        set_logger(self);
+        let default = level.default_styles();
+        self.styles.entry(level).or_insert(default).retain(|s| *s != style);
+        self.apply();
         return self.to_owned();
     }
 
@@ -438,8 +659,7 @@ impl Logger {
     /// # Returns
     /// Returns a vector of `Style` enums representing the styles associated with the specified log level.
     ///
-    /// # Panics
-    /// Panics if there are no styles associated with the specified log level.
+    /// Custom levels without styles of their own return the styles they were registered with.
     ///
     /// # Examples
     /// ```
@@ -449,7 +669,215 @@ impl Logger {
     /// let info_styles = logger.styles(Level::Info); // Retrieve styles for INFO level messages
     /// ```
     pub fn styles(&self, level: Level) -> Vec<Style> {
-        return self.styles.get(&level).expect("Magic has occured").clone();
+        return match self.styles.get(&level) {
+            Some(styles) => styles.clone(),
+            None => level.default_styles(),
+        };
+    }
+
+    /// Sets or clears the rate limit applied to each callsite.
+    ///
+    /// Messages over the limit are dropped and counted. When the limit lifts, a summary line such as
+    /// `suppressed 12,345 similar messages from app::net:88` is logged ahead of the callsite's next message. A
+    /// callsite which goes quiet after a flood logs no further message, so its summary waits for `flush()` - call it
+    /// before exiting, or periodically in long-running services. `Diagnostic` messages are never rate limited.
+    ///
+    /// # Arguments
+    /// * `limit` - The `RateLimit` to apply, or `None` to disable rate limiting.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let mut logger = Logger::new();
+    /// logger.rate_limit(RateLimit::new(10, 100)); // 10 messages per second, bursts of up to 100
+    /// logger.rate_limit(None); // Disable rate limiting
+    /// ```
+    pub fn rate_limit(&mut self, limit: impl Into<Option<RateLimit>>) -> Self {
+        self.rate_limit = limit.into();
+        self.apply();
+        return self.to_owned();
+    }
+
+    /// Enables or disables collapsing of consecutive duplicate messages written to the file - disabled by default.
+    ///
+    /// When the same level, module path and message repeat back to back, only the first one is written.
+    /// A `last message repeated N times` line follows once a different message arrives or the collapse timeout expires.
+    ///
+    /// # Arguments
+    /// * `value` - A boolean value where `true` enables collapsing and `false` disables it.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let mut logger = Logger::new();
+    /// logger.file_collapse(true); // Collapse duplicate messages in the log file
+    /// ```
+    pub fn file_collapse(&mut self, value: bool) -> Self {
+        self.file_collapse = value;
+        self.apply();
+        return self.to_owned();
+    }
+
+    /// Enables or disables collapsing of consecutive duplicate messages written to the terminal - disabled by default.
+    ///
+    /// When the same level, module path and message repeat back to back, only the first one is printed.
+    /// A `last message repeated N times` line follows once a different message arrives or the collapse timeout expires.
+    ///
+    /// # Arguments
+    /// * `value` - A boolean value where `true` enables collapsing and `false` disables it.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let mut logger = Logger::new();
+    /// logger.terminal_collapse(true); // Collapse duplicate messages in the terminal
+    /// ```
+    pub fn terminal_collapse(&mut self, value: bool) -> Self {
+        self.terminal_collapse = value;
+        self.apply();
+        return self.to_owned();
+    }
+
+    /// Sets how long a run of collapsed messages may last before its summary is written - 30 seconds by default.
+    ///
+    /// The summary is written as soon as the timeout expires, even if no further message is logged; use `flush()` to
+    /// write a pending summary immediately.
+    ///
+    /// # Arguments
+    /// * `timeout` - The maximum duration of a single run of collapsed messages.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    /// use std::time::Duration;
+    ///
+    /// let mut logger = Logger::new();
+    /// logger.collapse_timeout(Duration::from_secs(60)); // Summarize repeated messages at least once a minute
+    /// ```
+    pub fn collapse_timeout(&mut self, timeout: Duration) -> Self {
+        self.collapse_timeout = timeout;
+        self.apply();
+        return self.to_owned();
+    }
+
+    /// Adds a sampling rule to the list.
+    ///
+    /// Rules are checked in the order they were added and the first rule matching a message's level and module
+    /// decides whether it is kept. Messages that match no rule are always kept and `Diagnostic` messages are never sampled.
+    ///
+    /// # Arguments
+    /// * `rule` - The `SampleRule` to add.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let mut logger = Logger::new();
+    /// logger.sample(SampleRule::new(SampleRate::OneIn(100)).level(Level::Trace)); // Keep 1 in 100 trace messages
+    /// logger.log_format("[{timestamp} {level} {module_path} x{sample_rate}] {message}"); // Show the sampling rate
+    /// ```
+    pub fn sample(&mut self, rule: SampleRule) -> Self {
+        self.sampling.push(rule);
+        self.apply();
+        return self.to_owned();
+    }
+
+    /// Enables or disables rendering the thread's diagnostic context as structured fields - disabled by default.
+    ///
+    /// When enabled, values pushed with `logfather::context::push` are rendered ahead of each message's own fields.
+    /// The `{context}` placeholder is available either way.
+    ///
+    /// # Arguments
+    /// * `value` - A boolean value where `true` adds the context to the fields and `false` does not.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let mut logger = Logger::new();
+    /// logger.context_fields(true);
+    ///
+    /// let _request = logfather::context::push("request_id", 7);
+    /// info!(status = 200; "request served"); // ... request served request_id=7 status=200
+    /// ```
+    pub fn context_fields(&mut self, value: bool) -> Self {
+        self.context_fields = value;
+        self.apply();
+        return self.to_owned();
+    }
+
+    /// Sets the minimum level of log messages which capture a backtrace - `Level::None` (default) disables capturing.
+    ///
+    /// The backtrace is written below the log line. Capturing is skipped entirely for messages below the level, for
+    /// messages that are filtered out, sampled out or rate limited, and for `Diagnostic` messages.
+    ///
+    /// # Arguments
+    /// * `level` - The minimum `Level` of log messages which capture a backtrace.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let mut logger = Logger::new();
+    /// logger.backtrace(Level::Error); // Capture backtraces for Error and Critical messages
+    /// ```
+    pub fn backtrace(&mut self, level: Level) -> Self {
+        self.backtrace_level = level;
+        self.apply();
+        return self.to_owned();
+    }
+
+    /// Adds a filter every log message must pass to be written.
+    ///
+    /// Filters run after the level checks, in the order they were added, and receive the `Record` with its message,
+    /// fields and context filled in. `Diagnostic` messages bypass them.
+    ///
+    /// # Arguments
+    /// * `filter` - A predicate returning `true` to keep the record and `false` to drop it.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let mut logger = Logger::new();
+    /// logger.filter(|record| !record.module_path().starts_with("hyper")); // Silence a chatty dependency
+    /// ```
+    pub fn filter(&mut self, filter: impl Fn(&Record) -> bool + Send + Sync + 'static) -> Self {
+        self.filters.push(Filter(Arc::new(filter)));
+        self.apply();
+        return self.to_owned();
+    }
+
+    /// Sets a function rendering each log message in place of the `log_format` placeholders.
+    ///
+    /// The returned string is written as is, with any `{level}` left in it replaced by the output's level text.
+    ///
+    /// # Arguments
+    /// * `formatter` - A function rendering a `Record` into a line of output.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let mut logger = Logger::new();
+    /// logger.formatter(|record| format!("{} {{level}} {}:{} {}", record.timestamp().timestamp(), record.file(), record.line(), record.message()));
+    /// ```
+    pub fn formatter(&mut self, formatter: impl Fn(&Record) -> String + Send + Sync + 'static) -> Self {
+        self.formatter = Some(Formatter(Arc::new(formatter)));
+        self.apply();
+        return self.to_owned();
     }
 }
 
@@ -465,9 +893,15 @@ impl Logger {
 /// - `Critical`: Used for critical error messages that might require immediate attention.
 /// - `Diagnostic`: Used to bypass all filtering -- will not compile for release builds.
 /// - `None`: Special level used to disable logging.
+/// - `Custom(severity)`: A user-defined level - see `Level::register`. `Level::NOTICE`, `Level::AUDIT` and
+///   `Level::FATAL` are registered out of the box.
 ///
 /// # Values:
-/// None > Critical > Error > Warning > Info > Debug > Trace
+/// None (255) > Diagnostic (245) > FATAL (120) > AUDIT (110) > Critical (100) > Error (80) > Warning (60) > NOTICE (50) >
+/// Info (40) > Debug (20) > Trace (0)
+///
+/// Custom levels take the severities between the built-in ones, so they rank among them by severity. A `Custom` level
+/// given a built-in severity is that built-in level - it compares, hashes and is ignored the same way.
 ///
 /// # Examples
 ///
@@ -478,21 +912,152 @@ impl Logger {
 /// let mut logger = Logger::new();
 /// logger.level(Level::Error); // Only log errors and critical messages
 /// ```
-#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug)]
 pub enum Level {
-    Trace = 0,
-    Debug = 1,
-    Info = 2,
-    Warning = 3,
-    Error = 4,
-    Critical = 5,
-    Diagnostic = 245,
-    None = 255,
+    Trace,
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Critical,
+    Diagnostic,
+    None,
+    Custom(u8),
+}
+
+impl Level {
+    /// A custom level for normal but significant events, such as a configuration reload - between `Info` and `Warning`.
+    pub const NOTICE: Level = Level::Custom(50);
+
+    /// A custom level for security relevant events, ranked above `Critical` so they pass any built-in level filter.
+    pub const AUDIT: Level = Level::Custom(110);
+
+    /// A custom level for errors the application cannot recover from - the most severe level below `Diagnostic`.
+    pub const FATAL: Level = Level::Custom(120);
+
+    /// Returns the numeric severity of the level - higher values are more severe.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// assert!(Level::Error.severity() > Level::Info.severity());
+    /// ```
+    pub const fn severity(&self) -> u8 {
+        return match self {
+            Level::Trace => 0,
+            Level::Debug => 20,
+            Level::Info => 40,
+            Level::Warning => 60,
+            Level::Error => 80,
+            Level::Critical => 100,
+            Level::Diagnostic => 245,
+            Level::None => 255,
+            Level::Custom(severity) => *severity,
+        };
+    }
+
+    /// Returns the level with the given severity - a built-in level if one has it, otherwise `Level::Custom`.
+    ///
+    /// # Arguments
+    /// * `severity` - The numeric severity of the level.
+    pub const fn from_severity(severity: u8) -> Level {
+        return match severity {
+            0 => Level::Trace,
+            20 => Level::Debug,
+            40 => Level::Info,
+            60 => Level::Warning,
+            80 => Level::Error,
+            100 => Level::Critical,
+            245 => Level::Diagnostic,
+            255 => Level::None,
+            severity => Level::Custom(severity),
+        };
+    }
+
+    /// Registers a custom level with a display name and default terminal styles.
+    ///
+    /// Custom levels are filtered, ignored and styled like the built-in ones - loggers fall back to the
+    /// registered styles until `Logger::style` overrides them. The severities of the built-in levels are
+    /// reserved and keep their names, while registering `NOTICE`, `AUDIT` or `FATAL`'s severity renames it.
+    ///
+    /// # Arguments
+    /// * `severity` - The numeric severity of the level - e.g. 70 ranks between `Warning` and `Error`.
+    /// * `label` - The name displayed for the `{level}` placeholder.
+    /// * `styles` - The default terminal styles of the level.
+    ///
+    /// # Returns
+    /// Returns the registered `Level`.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// const SECURITY: Level = Level::Custom(115); // Between AUDIT and FATAL
+    /// Level::register(115, "SECURITY", vec![Style::Bold, Style::FGPurple]);
+    ///
+    /// log!(SECURITY, "user {} deleted", 42);
+    /// ```
+    pub fn register(severity: u8, label: &str, styles: Vec<Style>) -> Level {
+        let level = Level::from_severity(severity);
+        if let (Level::Custom(_), Ok(mut levels)) = (&level, LEVELS.write()) {
+            levels.insert(severity, (label.to_string(), styles));
+        }
+        return level;
+    }
+
+    /// Returns the default terminal styles of the level - empty for unregistered custom levels.
+    pub(crate) fn default_styles(&self) -> Vec<Style> {
+        return match Level::from_severity(self.severity()) {
+            Level::Trace => vec![Style::FGPurple],
+            Level::Debug => vec![Style::FGBlue],
+            Level::Info => vec![Style::FGGreen],
+            Level::Warning => vec![Style::FGYellow],
+            Level::Error => vec![Style::FGRed],
+            Level::Critical => vec![Style::Bold, Style::FGRed],
+            Level::Diagnostic => vec![Style::Bold, Style::FGCyan],
+            Level::None => vec![],
+            Level::Custom(severity) => match LEVELS.read() {
+                Ok(levels) => levels.get(&severity).map(|(_, styles)| styles.clone()).unwrap_or_default(),
+                Err(_) => vec![],
+            },
+        };
+    }
+}
+
+/// Levels are equal when their severities are, so `Level::Custom(40)` is `Level::Info`.
+impl PartialEq for Level {
+    fn eq(&self, other: &Self) -> bool {
+        return self.severity() == other.severity();
+    }
+}
+
+impl Eq for Level {}
+
+impl std::hash::Hash for Level {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.severity().hash(state);
+    }
+}
+
+impl PartialOrd for Level {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+/// Levels are ordered by severity.
+impl Ord for Level {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        return self.severity().cmp(&other.severity());
+    }
 }
 
 impl std::fmt::Display for Level {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
+        match Level::from_severity(self.severity()) {
             Level::Trace => write!(f, "TRACE"),
             Level::Debug => write!(f, "DEBUG"),
             Level::Info => write!(f, "INFO"),
@@ -501,10 +1066,51 @@ impl std::fmt::Display for Level {
             Level::Critical => write!(f, "CRITICAL"),
             Level::Diagnostic => write!(f, "DIAGNOSTIC"),
             Level::None => write!(f, "NONE"),
+            Level::Custom(severity) => match LEVELS.read().ok().and_then(|levels| levels.get(&severity).cloned()) {
+                Some((label, _)) => write!(f, "{}", label),
+                None => write!(f, "LEVEL{}", severity),
+            },
         }
     }
 }
 
+impl std::str::FromStr for Level {
+    type Err = LogfatherError;
+
+    /// Parses a level from its name, ignoring case.
+    ///
+    /// Accepts the built-in names and their macro aliases (`warn`, `crit`, `diag`), the labels of registered
+    /// custom levels, and `LEVEL<severity>` for unregistered ones.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let name = value.trim().to_uppercase();
+        let level = match name.as_str() {
+            "TRACE" => Level::Trace,
+            "DEBUG" => Level::Debug,
+            "INFO" => Level::Info,
+            "WARNING" | "WARN" => Level::Warning,
+            "ERROR" => Level::Error,
+            "CRITICAL" | "CRIT" => Level::Critical,
+            "DIAGNOSTIC" | "DIAG" => Level::Diagnostic,
+            "NONE" => Level::None,
+            _ => {
+                let registered = LEVELS.read().ok().and_then(|levels| {
+                    levels.iter().find(|(_, (label, _))| label.to_uppercase() == name).map(|(severity, _)| *severity)
+                });
+                match registered.or_else(|| name.strip_prefix("LEVEL").and_then(|n| n.parse().ok())) {
+                    Some(severity) => Level::from_severity(severity),
+                    None => {
+                        return Err(LogfatherError::ConfigError(format!(
+                            "unknown level `{}`, expected trace, debug, info, warning, error, critical, diagnostic, none or a registered custom level",
+                            value
+                        )))
+                    }
+                }
+            }
+        };
+        return Ok(level);
+    }
+}
+
 /// TimeZone selection.
 ///
 /// # Variants
@@ -521,6 +1127,7 @@ impl std::fmt::Display for Level {
 /// logger.timezone(TimeZone::Utc); // Sets timezone to UTC
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum TimeZone {
     Local,
     Utc,
@@ -546,188 +1153,698 @@ pub enum TimeZone {
 /// log(Level::Error, module_path!(), format_args!("An error occurred"));
 /// ```
 ///
-/// Note: In practice, prefer using the provided macros (`info!`, `warning!`, `error!`, `critical!`) for logging.
-pub fn log(level: Level, module_path: &str, args: std::fmt::Arguments) {
+/// Note: In practice, prefer using the provided macros (`info!`, `warning!`, `error!`, `critical!`) for logging.
+#[track_caller]
+pub fn log(level: Level, module_path: &str, args: std::fmt::Arguments) {
+    log_fields(level, module_path, args, &[]);
+}
+
+/// Logs a message with the specified log level, module path and structured fields.
+///
+/// Fields are appended to the message as ` key=value` pairs, or rendered wherever the `{fields}` placeholder
+/// appears in the log format.
+///
+/// # Arguments
+/// * `level` - The severity level of the log message.
+/// * `module_path` - The module path where the log message originates.
+/// * `message` - The log message broken into fragments.
+/// * `fields` - The structured key-value pairs attached to the message.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+/// use logfather::kv::Value;
+///
+/// // Example of manually logging a message with fields
+/// log_fields(Level::Info, module_path!(), format_args!("request served"), &[("user_id", Value::Display(&42))]);
+/// ```
+///
+/// Note: In practice, prefer using the provided macros (`info!(user_id = 42; "request served")`) for logging.
+#[track_caller]
+pub fn log_fields(level: Level, module_path: &str, args: std::fmt::Arguments, fields: &[Field]) {
+    log_at(level, module_path, module_path, args, fields, None, Location::caller());
+}
+
+/// Logs a message with the specified log level, module path, structured fields and error.
+///
+/// The error is appended to the message and every cause found through `Error::source()` is rendered
+/// on its own indented `caused by:` line.
+///
+/// # Arguments
+/// * `level` - The severity level of the log message.
+/// * `module_path` - The module path where the log message originates.
+/// * `message` - The log message broken into fragments.
+/// * `fields` - The structured key-value pairs attached to the message.
+/// * `error` - The error being logged.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let error = std::fs::read("missing.toml").unwrap_err();
+/// log_error(Level::Error, module_path!(), format_args!("failed to load"), &[], &error);
+/// ```
+///
+/// Note: In practice, prefer using the provided macros (`error!(err: e; "failed to load")`) for logging.
+#[track_caller]
+pub fn log_error(
+    level: Level,
+    module_path: &str,
+    args: std::fmt::Arguments,
+    fields: &[Field],
+    error: &dyn std::error::Error,
+) {
+    log_at(level, module_path, module_path, args, fields, Some(error), Location::caller());
+}
+
+/// Logs a message on behalf of an explicit callsite.
+pub(crate) fn log_at(
+    level: Level,
+    module_path: &str,
+    target: &str,
+    args: std::fmt::Arguments,
+    fields: &[Field],
+    error: Option<&dyn std::error::Error>,
+    location: &Location,
+) {
+    //Skip the lock entirely when the level is filtered out
+    if !enabled(&level) {
+        return;
+    }
+
+    let mut record = Record::new(level, module_path, args).with_target(target).with_fields(fields);
+    record.file = location.file();
+    record.line = location.line();
+    record.column = location.column();
+    record.error = error;
+    log_record(record);
+}
+
+/// Logs a message from a logging macro.
+#[doc(hidden)]
+#[track_caller]
+pub fn log_callsite(
+    level: Level,
+    module_path: &str,
+    target: Option<&str>,
+    args: std::fmt::Arguments,
+    fields: &[Field],
+    error: Option<&dyn std::error::Error>,
+) {
+    log_at(level, module_path, target.unwrap_or(module_path), args, fields, error, Location::caller());
+}
+
+/// Logs a prepared `Record`.
+///
+/// This is the entry point for integrations forwarding messages from other logging systems - the record keeps
+/// the level, module path and source location it was built with.
+///
+/// # Arguments
+/// * `record` - The log message to write.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let port = 8080;
+/// log_record(Record::new(Level::Info, "vendor::http", format_args!("listening on {}", port)).with_location("vendor/http.rs", 17));
+/// ```
+pub fn log_record(record: Record) {
+    //Skip the lock entirely when the level is filtered out
+    if !enabled(&record.level) {
+        return;
+    }
+
+    //Grab a clone of the logger to not hold up any other potential logging threads
+    let logger = LOGGER.read().expect("Could not read logger").clone();
+    logger.log(record);
+}
+
+/// Logs a message with the specified log level and module path.
+///
+/// The log message is formatted according to the logger's configuration and output to the designated targets (file and/or terminal).
+/// - Outputs a `LogfatherResult` in the event of failure instead of console outputs or panics.
+///
+/// # Arguments
+/// * `level` - The severity level of the log message.
+/// * `module_path` - The module path where the log message originates.
+/// * `message` - The log message broken into fragments.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// // Example of manually logging an error message
+/// let result = result_log(Level::Error, module_path!(), format_args!("An error occurred"));
+/// ```
+///
+/// Note: In practice, prefer using the provided macros (`info!`, `warning!`, `error!`, `critical!`) for logging.
+#[track_caller]
+pub fn result_log(level: Level, mod_path: &str, args: std::fmt::Arguments) -> LogfatherResult {
+    return result_log_fields(level, mod_path, args, &[]);
+}
+
+/// Logs a message with the specified log level, module path and structured fields.
+///
+/// Fields are appended to the message as ` key=value` pairs, or rendered wherever the `{fields}` placeholder
+/// appears in the log format.
+/// - Outputs a `LogfatherResult` in the event of failure instead of console outputs or panics.
+///
+/// # Arguments
+/// * `level` - The severity level of the log message.
+/// * `module_path` - The module path where the log message originates.
+/// * `message` - The log message broken into fragments.
+/// * `fields` - The structured key-value pairs attached to the message.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+/// use logfather::kv::Value;
+///
+/// // Example of manually logging a message with fields
+/// let result = result_log_fields(Level::Info, module_path!(), format_args!("request served"), &[("user_id", Value::Display(&42))]);
+/// ```
+///
+/// Note: In practice, prefer using the provided macros (`r_info!(user_id = 42; "request served")`) for logging.
+#[track_caller]
+pub fn result_log_fields(level: Level, mod_path: &str, args: std::fmt::Arguments, fields: &[Field]) -> LogfatherResult {
+    return result_log_at(level, mod_path, mod_path, args, fields, None, Location::caller());
+}
+
+/// Logs a message with the specified log level, module path, structured fields and error.
+///
+/// The error is appended to the message and every cause found through `Error::source()` is rendered
+/// on its own indented `caused by:` line.
+/// - Outputs a `LogfatherResult` in the event of failure instead of console outputs or panics.
+///
+/// # Arguments
+/// * `level` - The severity level of the log message.
+/// * `module_path` - The module path where the log message originates.
+/// * `message` - The log message broken into fragments.
+/// * `fields` - The structured key-value pairs attached to the message.
+/// * `error` - The error being logged.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let error = std::fs::read("missing.toml").unwrap_err();
+/// let result = result_log_error(Level::Error, module_path!(), format_args!("failed to load"), &[], &error);
+/// ```
+///
+/// Note: In practice, prefer using the provided macros (`r_error!(err: e; "failed to load")`) for logging.
+#[track_caller]
+pub fn result_log_error(
+    level: Level,
+    mod_path: &str,
+    args: std::fmt::Arguments,
+    fields: &[Field],
+    error: &dyn std::error::Error,
+) -> LogfatherResult {
+    return result_log_at(level, mod_path, mod_path, args, fields, Some(error), Location::caller());
+}
+
+/// Logs a message on behalf of an explicit callsite.
+pub(crate) fn result_log_at(
+    level: Level,
+    mod_path: &str,
+    target: &str,
+    args: std::fmt::Arguments,
+    fields: &[Field],
+    error: Option<&dyn std::error::Error>,
+    location: &Location,
+) -> LogfatherResult {
+    //Skip building the record when the level is filtered out
+    if !enabled(&level) {
+        return Ok(());
+    }
+
+    let mut record = Record::new(level, mod_path, args).with_target(target).with_fields(fields);
+    record.file = location.file();
+    record.line = location.line();
+    record.column = location.column();
+    record.error = error;
+    return result_log_record(record);
+}
+
+/// Logs a message from a `r_` logging macro.
+#[doc(hidden)]
+#[track_caller]
+pub fn result_log_callsite(
+    level: Level,
+    module_path: &str,
+    target: Option<&str>,
+    args: std::fmt::Arguments,
+    fields: &[Field],
+    error: Option<&dyn std::error::Error>,
+) -> LogfatherResult {
+    return result_log_at(level, module_path, target.unwrap_or(module_path), args, fields, error, Location::caller());
+}
+
+/// Logs a prepared `Record`.
+///
+/// This is the entry point for integrations forwarding messages from other logging systems - the record keeps
+/// the level, module path and source location it was built with.
+/// - Outputs a `LogfatherResult` in the event of failure instead of console outputs or panics.
+///
+/// # Arguments
+/// * `record` - The log message to write.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let port = 8080;
+/// let result = result_log_record(Record::new(Level::Info, "vendor::http", format_args!("listening on {}", port)));
+/// ```
+pub fn result_log_record(record: Record) -> LogfatherResult {
+    //Skip the lock entirely when the level is filtered out
+    if !enabled(&record.level) {
+        return Ok(());
+    }
+
     //Grab a clone of the logger to not hold up any other potential logging threads
-    let logger = LOGGER.read().expect("Could not read logger").clone();
+    let logger = LOGGER.read().map_err(LogfatherError::from)?.clone();
+    return logger.result_log(record);
+}
 
-    //If the level is too low then return
-    if level < logger.output_level || logger.ignore.contains(&level) {
-        return;
+impl Logger {
+    /// Logs a prepared `Record` through this logger instead of the global one.
+    ///
+    /// Combined with `Logger::standalone`, this lets a library own its logger without replacing the application's.
+    /// Prefer the `logger:` form of the logging macros, e.g. `info!(logger: &logger, "...")`.
+    ///
+    /// # Arguments
+    /// * `record` - The log message to write.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let logger = Logger::standalone().path("my_lib.log").file(true).terminal(false);
+    /// logger.log(Record::new(Level::Info, module_path!(), format_args!("connected")));
+    /// ```
+    pub fn log(&self, record: Record) {
+        if let Err(e) = self.dispatch(record, Failure::Panic) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Logs a prepared `Record` through this logger instead of the global one.
+    /// - Outputs a `LogfatherResult` in the event of failure instead of console outputs or panics.
+    ///
+    /// # Arguments
+    /// * `record` - The log message to write.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let logger = Logger::standalone();
+    /// let result = logger.result_log(Record::new(Level::Info, module_path!(), format_args!("connected")));
+    /// ```
+    pub fn result_log(&self, record: Record) -> LogfatherResult {
+        return self.dispatch(record, Failure::Return);
     }
 
-    let message = format!("{}", args);
+    /// Runs a record through the filters, sampling and rate limiting and writes it out.
+    pub(crate) fn dispatch(&self, record: Record, failure: Failure) -> LogfatherResult {
+        return self.route(record, failure, false);
+    }
 
-    //Get the time
-    let time = match logger.timezone {
-        TimeZone::Local => {
-            let now = Local::now();
-            s!(now.format(&logger.timestamp_format))
+    /// Writes out a record which must not be lost, such as a panic, without running it through the filters,
+    /// sampling and rate limiting.
+    pub(crate) fn dispatch_unfiltered(&self, record: Record) -> LogfatherResult {
+        return self.route(record, Failure::Return, true);
+    }
+
+    fn route(&self, mut record: Record, failure: Failure, bypass: bool) -> LogfatherResult {
+        //If the level is too low then return
+        if !self.accepts(&record.level) {
+            return Ok(());
         }
-        TimeZone::Utc => {
-            let now = Utc::now();
-            s!(now.format(&logger.timestamp_format))
+
+        //The error follows the message and its causes get lines of their own
+        record.message = match record.error {
+            Some(error) => {
+                record.causes = error::causes(error);
+                match s!(record.args) {
+                    message if message.is_empty() => s!(error),
+                    message => format!("{}: {}", message, error),
+                }
+            }
+            None => s!(record.args),
+        };
+        record.context = context::current();
+        //Records forwarded from other systems may bring their own span path
+        if record.span.is_empty() {
+            record.span = span::path();
         }
-    };
 
-    //Replace the relevant sections in the format
-    let log_format = logger
-        .log_format
-        .replace("{timestamp}", &time)
-        .replace("{module_path}", module_path)
-        .replace("{message}", &message);
+        //Diagnostic messages bypass the user's filters, sampling and rate limiting
+        let diagnostic = record.level == Level::Diagnostic;
+        let bypass = bypass || diagnostic;
+        if !bypass && !self.filters.iter().all(|filter| (filter.0)(&record)) {
+            return Ok(());
+        }
 
-    //Only write to the file if both of these are true
-    if logger.file_output && !logger.file_ignore.contains(&level) {
-        if let Some(mut path) = logger.path {
-            // Handle empty path
-            if path.as_os_str().is_empty() {
-                path = std::env::current_dir()
-                    .unwrap_or_else(|_| panic!(
-                        "{}\n{}",
-                        "ERROR: No path given. Attempted to write to current directory.",
-                        "  FAILURE: insufficient permissions or the current directory does not exist."
-                    ));
-                // Append default file name
-                path.push(".logger");
+        //Drop the message if it is sampled out
+        if !bypass {
+            match sample::sample(&self.sampling, &record.level, record.target) {
+                Some(rate) => record.sample_rate = rate,
+                None => return Ok(()),
             }
+        }
 
-            // Check if the path contains directory separators indicating multiple directories
-            if let Some(parent) = PathBuf::from(&path).parent() {
-                std::fs::create_dir_all(parent).expect("failed to create missing sub-directories");
+        //Drop the message if its callsite is over the rate limit
+        if let (Some(limit), false) = (&self.rate_limit, bypass) {
+            match rate_limit::check(&self.limiter, limit, &record) {
+                Verdict::Suppress => return Ok(()),
+                Verdict::Emit(0) => {}
+                Verdict::Emit(suppressed) => {
+                    let summary = rate_limit::summary(suppressed, &record);
+                    write(self, &record.summary(record.level.clone(), record.target, summary), failure)?;
+                }
             }
+        }
 
-            let file = std::fs::OpenOptions::new()
-                .create(true)
-                .read(true)
-                .append(true)
-                .open(&path);
-
-            let file = match file {
-                Ok(f) => f,
-                Err(_e) => std::fs::File::create(path).expect("Could not create file"),
-            };
-
-            //Output-specific level replacement
-            let format = log_format.replace("{level}", &s!(level));
-
-            //Lock down the file while it's being written to in case multithreaded application
-            let file_mutex = std::sync::Mutex::new(file);
-            {
-                let mut file = file_mutex.lock().unwrap();
-                _ = writeln!(file, "{}", format);
-            }
+        //Only pay for a backtrace at or above the configured level, once the message is certain to be written
+        if !diagnostic && self.backtrace_level != Level::None && record.level >= self.backtrace_level {
+            record.backtrace = Some(Backtrace::force_capture());
         }
+
+        return write(self, &record, failure);
     }
 
-    //Terminal output
-    if logger.terminal_output && !logger.terminal_ignore.contains(&level) {
-        // Set color
-        let styles = logger.styles.get(&level).unwrap();
+    /// Logs a message from a logging macro through this logger.
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn log_callsite(
+        &self,
+        level: Level,
+        module_path: &str,
+        target: Option<&str>,
+        args: std::fmt::Arguments,
+        fields: &[Field],
+        error: Option<&dyn std::error::Error>,
+    ) {
+        //Skip building the record when the level is filtered out
+        if !self.accepts(&level) {
+            return;
+        }
+
+        let mut record = Record::new(level, module_path, args).with_fields(fields);
+        record.target = target.unwrap_or(module_path);
+        record.error = error;
+        self.log(record);
+    }
+
+    /// Logs a message from a `r_` logging macro through this logger.
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn result_log_callsite(
+        &self,
+        level: Level,
+        module_path: &str,
+        target: Option<&str>,
+        args: std::fmt::Arguments,
+        fields: &[Field],
+        error: Option<&dyn std::error::Error>,
+    ) -> LogfatherResult {
+        //Skip building the record when the level is filtered out
+        if !self.accepts(&level) {
+            return Ok(());
+        }
 
-        // Output-specific level replacement
-        let format = log_format.replace("{level}", &style(styles.clone(), level));
+        let mut record = Record::new(level, module_path, args).with_fields(fields);
+        record.target = target.unwrap_or(module_path);
+        record.error = error;
+        return self.result_log(record);
+    }
 
-        //Print to the terminal
-        println!("{}", format);
+    /// Checks the level against this logger's output level and ignored levels.
+    fn accepts(&self, level: &Level) -> bool {
+        return *level >= self.output_level && !self.ignore.contains(level);
     }
 }
 
-/// Logs a message with the specified log level and module path.
-///
-/// The log message is formatted according to the logger's configuration and output to the designated targets (file and/or terminal).
-/// - Outputs a `LogfatherResult` in the event of failure instead of console outputs or panics.
+/// Writes out any summaries of repeated or rate limited messages that are still pending in the global logger.
 ///
-/// # Arguments
-/// * `level` - The severity level of the log message.
-/// * `module_path` - The module path where the log message originates.
-/// * `message` - The log message broken into fragments.
+/// Runs of repeated messages are normally summarized once a different message arrives or the collapse
+/// timeout expires, and suppressed messages once their callsite logs again. Call this before exiting to make
+/// sure the final `last message repeated N times` and `suppressed N similar messages` lines are not lost.
+/// Standalone loggers are flushed with `Logger::flush`.
 ///
 /// # Examples
 ///
 /// ``` no_run
 /// use logfather::*;
 ///
-/// // Example of manually logging an error message
-/// let result = result_log(Level::Error, module_path!(), format_args!("An error occurred"));
-/// ```
+/// let mut logger = Logger::new();
+/// logger.terminal_collapse(true);
 ///
-/// Note: In practice, prefer using the provided macros (`info!`, `warning!`, `error!`, `critical!`) for logging.
-pub fn result_log(level: Level, mod_path: &str, args: std::fmt::Arguments) -> LogfatherResult {
-    //Grab a clone of the logger to not hold up any other potential logging threads
+/// for _ in 0..10 {
+///     info!("Polling...");
+/// }
+/// flush().expect("Could not flush the logger"); // Writes "last message repeated 9 times"
+/// ```
+pub fn flush() -> LogfatherResult {
     let logger = LOGGER.read().map_err(LogfatherError::from)?.clone();
+    return logger.flush();
+}
 
-    //If the level is too low then return
-    if level < logger.output_level || logger.ignore.contains(&level) {
-        return Ok(());
+impl Logger {
+    /// Writes out any summaries of repeated or rate limited messages that are still pending in this logger.
+    ///
+    /// Clones of a logger share its pending summaries, so flushing any of them flushes them all.
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use logfather::*;
+    ///
+    /// let logger = Logger::standalone().terminal_collapse(true);
+    /// for _ in 0..10 {
+    ///     info!(logger: &logger, "Polling...");
+    /// }
+    /// logger.flush().expect("Could not flush the logger"); // Writes "last message repeated 9 times"
+    /// ```
+    pub fn flush(&self) -> LogfatherResult {
+        for output in [Output::File, Output::Terminal] {
+            if let Some(pending) = collapse::drain(&self.collapser, output) {
+                let mut record = Record::new(pending.level.clone(), &pending.module_path, format_args!(""));
+                record.message = pending.message();
+                write_to(self, output, &record, Failure::Return)?;
+            }
+        }
+
+        for suppressed in rate_limit::drain(&self.limiter) {
+            let mut record = Record::new(suppressed.level.clone(), &suppressed.target, format_args!(""));
+            record.message = suppressed.message();
+            write(self, &record, Failure::Return)?;
+        }
+
+        return std::io::stdout().flush().map_err(LogfatherError::from);
+    }
+
+    /// Writes out the summaries of runs of repeated messages whose collapse timeout has expired.
+    fn expire_collapsed(&self) {
+        for (output, pending) in collapse::expire(&self.collapser, self.collapse_timeout) {
+            let mut record = Record::new(pending.level.clone(), &pending.module_path, format_args!(""));
+            record.message = pending.message();
+            // Runs on the collapse timer, where there is nobody to report a failure to
+            _ = write_to(self, output, &record, Failure::Return);
+        }
+    }
+}
+
+/// How a failure to write a message is surfaced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Failure {
+    /// The error is returned - used by the `r_` macros and `result_log` functions.
+    Return,
+    /// Missing directories and unopenable files panic and write errors are ignored - used by the plain macros.
+    Panic,
+}
+
+/// Writes a single record to every enabled output.
+fn write(logger: &Logger, record: &Record, failure: Failure) -> LogfatherResult {
+    //Only write to the file if both of these are true
+    if logger.file_output && !logger.file_ignore.contains(&record.level) {
+        write_collapsed(logger, Output::File, logger.file_collapse, record, failure)?;
+    }
+
+    //Terminal output
+    if logger.terminal_output && !logger.terminal_ignore.contains(&record.level) {
+        write_collapsed(logger, Output::Terminal, logger.terminal_collapse, record, failure)?;
+    }
+
+    return Ok(());
+}
+
+/// Writes a record to a single output, collapsing consecutive repeats if enabled for that output.
+fn write_collapsed(logger: &Logger, output: Output, collapse: bool, record: &Record, failure: Failure) -> LogfatherResult {
+    if collapse {
+        // Messages only repeat if their fields do as well
+        let message = format!("{}{}", record.message, kv::render(record.fields));
+        match collapse::observe(&logger.collapser, output, &record.level, record.target, &message, logger.collapse_timeout) {
+            Observed::Repeat => return Ok(()),
+            Observed::FirstRepeat(due) => {
+                // Summarize the run once it times out, even if no other message comes along to end it
+                let logger = logger.clone();
+                collapse::schedule(due, Box::new(move || logger.expire_collapsed()));
+                return Ok(());
+            }
+            Observed::New(Some(pending)) => {
+                let summary = record.summary(pending.level.clone(), &pending.module_path, pending.message());
+                write_to(logger, output, &summary, failure)?;
+            }
+            Observed::New(None) => {}
+        }
     }
 
-    let message = format!("{}", args);
+    return write_to(logger, output, record, failure);
+}
 
+/// Renders a record with the logger's format, leaving the `{level}` placeholder to the output.
+fn format(logger: &Logger, record: &Record) -> String {
     //Get the time
     let time = match logger.timezone {
-        TimeZone::Local => {
-            let now = Local::now();
-            s!(now.format(&logger.timestamp_format))
-        }
-        TimeZone::Utc => {
-            let now = Utc::now();
-            s!(now.format(&logger.timestamp_format))
-        }
+        TimeZone::Local => s!(record.timestamp.with_timezone(&Local).format(&logger.timestamp_format)),
+        TimeZone::Utc => s!(record.timestamp.format(&logger.timestamp_format)),
     };
 
+    //Render the thread's context, optionally merging it into the fields
+    let context: Vec<Field> = record.context.iter().map(|(k, v)| (k.as_str(), Value::Display(v))).collect();
+    let fields = match logger.context_fields {
+        true => kv::render(&[context.as_slice(), record.fields].concat()),
+        false => kv::render(record.fields),
+    };
+
+    //Fields go wherever the format asks for them, otherwise they trail the message
+    let mut log_format = logger.log_format.clone();
+    if !log_format.contains("{fields}") && !fields.is_empty() {
+        log_format.push_str(" {fields}");
+    }
+    let context = kv::render(&context);
+
+    //Empty placeholders take one of their surrounding spaces with them
+    for (placeholder, value) in [("{fields}", &fields), ("{context}", &context), ("{span}", &record.span)] {
+        if value.is_empty() {
+            log_format = log_format
+                .replace(&format!("{} ", placeholder), "")
+                .replace(&format!(" {}", placeholder), "")
+                .replace(placeholder, "");
+        }
+    }
+
     //Replace the relevant sections in the format
-    let log_format = logger
-        .log_format
+    let mut log_format = log_format
         .replace("{timestamp}", &time)
-        .replace("{module_path}", mod_path)
-        .replace("{message}", &message);
+        .replace("{module_path}", record.target)
+        .replace("{target}", record.target)
+        .replace("{sample_rate}", &record.sample_rate.to_string())
+        .replace("{fields}", &fields)
+        .replace("{context}", &context)
+        .replace("{span}", &record.span)
+        .replace("{message}", &record.message);
+
+    //Error causes are indented below the line
+    for cause in &record.causes {
+        log_format.push_str("\n  caused by: ");
+        log_format.push_str(cause);
+    }
 
-    //Only write to the file if both of these are true
-    if logger.file_output && !logger.file_ignore.contains(&level) {
-        if let Some(mut path) = logger.path {
-            // Handle empty path
-            if path.as_os_str().is_empty() {
-                // Get the current directory
-                path = std::env::current_dir().map_err(LogfatherError::from)?;
-                // Append default file name
-                path.push(".logger");
-            }
+    //Backtraces go below everything else
+    if let Some(backtrace) = &record.backtrace {
+        log_format.push('\n');
+        log_format.push_str(s!(backtrace).trim_end());
+    }
 
-            // Check if the path contains directory separators indicating multiple directories
-            if let Some(parent) = PathBuf::from(&path).parent() {
-                std::fs::create_dir_all(parent).map_err(LogfatherError::from)?;
-            }
+    return log_format;
+}
 
-            let file = std::fs::OpenOptions::new()
-                .create(true)
-                .read(true)
-                .append(true)
-                .open(&path)
-                .map_err(LogfatherError::from)?;
-
-            //Output-specific level replacement
-            let format = log_format.replace("{level}", &s!(level));
-
-            //Lock down the file while it's being written to in case multithreaded application
-            let file_mutex = std::sync::Mutex::new(file);
-            {
-                let mut file = file_mutex.lock().map_err(LogfatherError::from)?;
-                writeln!(file, "{}", format).map_err(LogfatherError::from)?;
+/// Formats a single record and writes it to the given output.
+fn write_to(logger: &Logger, output: Output, record: &Record, failure: Failure) -> LogfatherResult {
+    //A custom formatter takes over the whole line
+    let log_format = match &logger.formatter {
+        Some(formatter) => (formatter.0)(record),
+        None => format(logger, record),
+    };
+
+    match output {
+        Output::File => {
+            if let Some(mut path) = logger.path.clone() {
+                // Handle empty path
+                if path.as_os_str().is_empty() {
+                    // Get the current directory
+                    path = match failure {
+                        Failure::Return => std::env::current_dir().map_err(LogfatherError::from)?,
+                        Failure::Panic => std::env::current_dir().unwrap_or_else(|_| {
+                            panic!(
+                                "{}\n{}",
+                                "ERROR: No path given. Attempted to write to current directory.",
+                                "  FAILURE: insufficient permissions or the current directory does not exist."
+                            )
+                        }),
+                    };
+                    // Append default file name
+                    path.push(".logger");
+                }
+
+                // Check if the path contains directory separators indicating multiple directories
+                if let Some(parent) = PathBuf::from(&path).parent() {
+                    match failure {
+                        Failure::Return => std::fs::create_dir_all(parent).map_err(LogfatherError::from)?,
+                        Failure::Panic => std::fs::create_dir_all(parent).expect("failed to create missing sub-directories"),
+                    }
+                }
+
+                let file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .read(true)
+                    .append(true)
+                    .open(&path);
+
+                let file = match (file, failure) {
+                    (Ok(f), _) => f,
+                    (Err(e), Failure::Return) => return Err(LogfatherError::from(e)),
+                    (Err(_e), Failure::Panic) => std::fs::File::create(path).expect("Could not create file"),
+                };
+
+                //Output-specific level replacement
+                let format = log_format.replace("{level}", &s!(record.level));
+
+                //Lock down the file while it's being written to in case multithreaded application
+                let file_mutex = std::sync::Mutex::new(file);
+                {
+                    let mut file = file_mutex.lock().map_err(LogfatherError::from)?;
+                    match failure {
+                        Failure::Return => writeln!(file, "{}", format).map_err(LogfatherError::from)?,
+                        Failure::Panic => _ = writeln!(file, "{}", format),
+                    }
+                }
             }
         }
-    }
-
-    //Terminal output
-    if logger.terminal_output && !logger.terminal_ignore.contains(&level) {
-        // Set color
-        let styles = logger.styles.get(&level).unwrap();
+        Output::Terminal => {
+            // Set color
+            let styles = logger.styles(record.level.clone());
 
-        // Output-specific level replacement
-        let format = log_format.replace("{level}", &style(styles.clone(), level));
+            // Output-specific level replacement
+            let format = log_format.replace("{level}", &style(styles, &record.level));
 
-        //Print to the terminal
-        println!("{}", format);
+            //Print to the terminal
+            println!("{}", format);
+        }
     }
 
     return Ok(());
@@ -735,6 +1852,49 @@ pub fn result_log(level: Level, mod_path: &str, args: std::fmt::Arguments) -> Lo
 
 // ##################################################################### Macro Definitions #####################################################################
 
+/// Checks whether messages of a level would currently be logged.
+///
+/// The check only reads a few atomics and never locks or clones the global logger, so it can be used to skip
+/// expensive preparation of log arguments. Levels compiled out with the `max_level_*` features are always disabled.
+///
+/// # Example
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// if log_enabled!(Level::Trace) {
+///     let state = vec![1, 2, 3]; // Expensive to build
+///     trace!("Current state: {:?}", state);
+/// }
+/// ```
+#[macro_export]
+macro_rules! log_enabled {
+    ($level:expr) => {{
+        let level = $level;
+        level.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() && $crate::logger::enabled(&level)
+    }};
+}
+
+/// Logs a message at any level, including custom levels registered with `Level::register`.
+///
+/// # Example
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// log!(Level::AUDIT, "user {} deleted", 42);
+/// log!(Level::Info, target: "audit", "user {} restored", 42);
+/// ```
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        if level.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(log, level, $($arg)+)
+        }
+    }};
+}
+
 /// Logs a message for tracing - very low priority.
 ///
 /// # Example
@@ -747,7 +1907,9 @@ pub fn result_log(level: Level, mod_path: &str, args: std::fmt::Arguments) -> Lo
 #[macro_export]
 macro_rules! trace {
     ($($arg:tt)*) => {{
-        $crate::log($crate::Level::Trace, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Trace.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(log, $crate::Level::Trace, $($arg)*)
+        }
     }};
 }
 
@@ -765,24 +1927,36 @@ macro_rules! debug {
     ($($arg:tt)*) => {
         #[cfg(debug_assertions)]
         {
-        $crate::log($crate::Level::Debug, module_path!(), format_args!($($arg)*))
+            if $crate::Level::Debug.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+                $crate::__log!(log, $crate::Level::Debug, $($arg)*)
+            }
         }
     };
 }
 
 /// Logs an informational message.
 ///
+/// Like every logging macro, it accepts a leading `logger:` which logs through that `Logger` instead of the
+/// global one, and a `target:` which replaces the module path for filtering, sampling and the
+/// `{module_path}`/`{target}` placeholders.
+///
 /// # Example
 ///
 /// ``` no_run
-/// use logfather::info;
+/// use logfather::*;
 ///
 /// info!("This is an info message");
+/// info!(target: "audit", "user {} deleted", 42);
+///
+/// let logger = Logger::standalone();
+/// info!(logger: &logger, target: "audit", "user {} restored", 42);
 /// ```
 #[macro_export]
 macro_rules! info {
     ($($arg:tt)*) => {{
-        $crate::log($crate::Level::Info, module_path!(), format_args!($($arg)*));
+        if $crate::Level::Info.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(log, $crate::Level::Info, $($arg)*);
+        }
     }};
 }
 
@@ -800,7 +1974,9 @@ macro_rules! info {
 #[macro_export]
 macro_rules! warning {
     ($($arg:tt)*) => {{
-        $crate::log($crate::Level::Warning, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Warning.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(log, $crate::Level::Warning, $($arg)*)
+        }
     }};
 }
 
@@ -818,7 +1994,9 @@ macro_rules! warning {
 #[macro_export]
 macro_rules! warn {
     ($($arg:tt)*) => {{
-        $crate::log($crate::Level::Warning, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Warning.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(log, $crate::Level::Warning, $($arg)*)
+        }
     }};
 }
 
@@ -833,10 +2011,22 @@ macro_rules! warn {
 /// ```
 ///
 /// Use this macro for logging errors, typically when an operation fails or an unexpected condition occurs.
+/// Prefix the arguments with `err: e;` to append an error and render its `source()` chain as `caused by:` lines. Any
+/// `Error` is accepted, as well as `Box<dyn Error>`, `anyhow::Error` and other types implementing `AsRef<dyn Error>`.
+///
+/// ``` no_run
+/// use logfather::error;
+///
+/// if let Err(e) = std::fs::read("config.toml") {
+///     error!(err: e; "failed to load config");
+/// }
+/// ```
 #[macro_export]
 macro_rules! error {
     ($($arg:tt)*) => {{
-        $crate::log($crate::Level::Error, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Error.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(log, $crate::Level::Error, $($arg)*)
+        }
     }};
 }
 
@@ -854,7 +2044,9 @@ macro_rules! error {
 #[macro_export]
 macro_rules! critical {
     ($($arg:tt)*) => {{
-        $crate::log($crate::Level::Critical, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Critical.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(log, $crate::Level::Critical, $($arg)*)
+        }
     }};
 }
 
@@ -872,7 +2064,9 @@ macro_rules! critical {
 #[macro_export]
 macro_rules! crit {
     ($($arg:tt)*) => {{
-        $crate::log($crate::Level::Critical, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Critical.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(log, $crate::Level::Critical, $($arg)*)
+        }
     }};
 }
 
@@ -890,7 +2084,9 @@ macro_rules! diagnostic {
     ($($arg:tt)*) => {
         #[cfg(debug_assertions)]
         {
-            $crate::log($crate::Level::Diagnostic, module_path!(), format_args!($($arg)*))
+            if $crate::Level::Diagnostic.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+                $crate::__log!(log, $crate::Level::Diagnostic, $($arg)*)
+            }
         }
     };
 }
@@ -909,11 +2105,37 @@ macro_rules! diag {
     ($($arg:tt)*) => {
         #[cfg(debug_assertions)]
         {
-            $crate::log($crate::Level::Diagnostic, module_path!(), format_args!($($arg)*))
+            if $crate::Level::Diagnostic.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+                $crate::__log!(log, $crate::Level::Diagnostic, $($arg)*)
+            }
         }
     };
 }
 
+/// Logs a message at any level, including custom levels registered with `Level::register`.
+///
+/// # Example
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let result = r_log!(Level::AUDIT, "user {} deleted", 42);
+/// if result.is_err() {
+///     println!("The log failed.");
+/// }
+/// ```
+#[macro_export]
+macro_rules! r_log {
+    ($level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        if level.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(result_log, level, $($arg)+)
+        } else {
+            Ok(())
+        }
+    }};
+}
+
 /// Logs a message for tracing - very low priority.
 ///
 /// # Example
@@ -929,7 +2151,11 @@ macro_rules! diag {
 #[macro_export]
 macro_rules! r_trace {
     ($($arg:tt)*) => {{
-        $crate::result_log($crate::Level::Trace, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Trace.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(result_log, $crate::Level::Trace, $($arg)*)
+        } else {
+            Ok(())
+        }
     }};
 }
 
@@ -950,11 +2176,15 @@ macro_rules! r_debug {
     ($($arg:tt)*) => {{
         #[cfg(debug_assertions)]
         {
-            $crate::result_log($crate::Level::Debug, module_path!(), format_args!($($arg)*))
+            if $crate::Level::Debug.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+                $crate::__log!(result_log, $crate::Level::Debug, $($arg)*)
+            } else {
+                Ok(())
+            }
         }
         #[cfg(not(debug_assertions))]
         {
-            Ok::<(), LogfatherError>(())
+            Ok::<(), $crate::LogfatherError>(())
         }
     }};
 }
@@ -974,7 +2204,11 @@ macro_rules! r_debug {
 #[macro_export]
 macro_rules! r_info {
     ($($arg:tt)*) => {{
-        $crate::result_log($crate::Level::Info, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Info.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(result_log, $crate::Level::Info, $($arg)*)
+        } else {
+            Ok(())
+        }
     }};
 }
 
@@ -995,7 +2229,11 @@ macro_rules! r_info {
 #[macro_export]
 macro_rules! r_warning {
     ($($arg:tt)*) => {{
-        $crate::result_log($crate::Level::Warning, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Warning.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(result_log, $crate::Level::Warning, $($arg)*)
+        } else {
+            Ok(())
+        }
     }};
 }
 
@@ -1016,7 +2254,11 @@ macro_rules! r_warning {
 #[macro_export]
 macro_rules! r_warn {
     ($($arg:tt)*) => {{
-        $crate::result_log($crate::Level::Warning, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Warning.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(result_log, $crate::Level::Warning, $($arg)*)
+        } else {
+            Ok(())
+        }
     }};
 }
 
@@ -1037,7 +2279,11 @@ macro_rules! r_warn {
 #[macro_export]
 macro_rules! r_error {
     ($($arg:tt)*) => {{
-        $crate::result_log($crate::Level::Error, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Error.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(result_log, $crate::Level::Error, $($arg)*)
+        } else {
+            Ok(())
+        }
     }};
 }
 
@@ -1058,7 +2304,11 @@ macro_rules! r_error {
 #[macro_export]
 macro_rules! r_critical {
     ($($arg:tt)*) => {{
-        $crate::result_log($crate::Level::Critical, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Critical.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(result_log, $crate::Level::Critical, $($arg)*)
+        } else {
+            Ok(())
+        }
     }};
 }
 
@@ -1079,7 +2329,11 @@ macro_rules! r_critical {
 #[macro_export]
 macro_rules! r_crit {
     ($($arg:tt)*) => {{
-        $crate::result_log($crate::Level::Critical, module_path!(), format_args!($($arg)*))
+        if $crate::Level::Critical.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+            $crate::__log!(result_log, $crate::Level::Critical, $($arg)*)
+        } else {
+            Ok(())
+        }
     }};
 }
 
@@ -1100,11 +2354,15 @@ macro_rules! r_diagnostic {
     ($($arg:tt)*) => {{
         #[cfg(debug_assertions)]
         {
-            $crate::result_log($crate::Level::Diagnostic, module_path!(), format_args!($($arg)*))
+            if $crate::Level::Diagnostic.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+                $crate::__log!(result_log, $crate::Level::Diagnostic, $($arg)*)
+            } else {
+                Ok(())
+            }
         }
         #[cfg(not(debug_assertions))]
         {
-            Ok::<(), LogfatherError>(())
+            Ok::<(), $crate::LogfatherError>(())
         }
     }};
 }
@@ -1126,22 +2384,68 @@ macro_rules! r_diag {
     ($($arg:tt)*) => {{
         #[cfg(debug_assertions)]
         {
-            $crate::result_log($crate::Level::Diagnostic, module_path!(), format_args!($($arg)*))
+            if $crate::Level::Diagnostic.severity() >= $crate::STATIC_OUTPUT_LEVEL.severity() {
+                $crate::__log!(result_log, $crate::Level::Diagnostic, $($arg)*)
+            } else {
+                Ok(())
+            }
         }
         #[cfg(not(debug_assertions))]
         {
-            Ok::<(), LogfatherError>(())
+            Ok::<(), $crate::LogfatherError>(())
         }
     }};
 }
 // ##################################################################### Test #####################################################################
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
+    /// Serialises the tests which replace the global logger, so none of them swaps it out from under another.
+    static GLOBAL: Mutex<()> = Mutex::new(());
+
+    /// Holds the global logger for the rest of a test - a test failing while holding it doesn't block the others.
+    pub(crate) fn lock_global() -> std::sync::MutexGuard<'static, ()> {
+        return GLOBAL.lock().unwrap_or_else(|e| e.into_inner());
+    }
+
+    /// Runs `log` against a global logger capturing `target file:line [span] message fields` of every record whose
+    /// target starts with `prefix`, then restores the previous global logger.
+    pub(crate) fn capture(prefix: &'static str, log: impl FnOnce()) -> Vec<String> {
+        return capture_with(Logger::standalone(), prefix, log);
+    }
+
+    /// Like `capture`, installing `logger` as the global logger - only records it writes out are captured.
+    pub(crate) fn capture_with(mut logger: Logger, prefix: &'static str, log: impl FnOnce()) -> Vec<String> {
+        let _global = lock_global();
+        let previous = global().unwrap();
+
+        let seen = Arc::new(Mutex::new(vec![]));
+        let captured = seen.clone();
+        set_logger(&logger.terminal(true).formatter(move |record| {
+            if record.target().starts_with(prefix) {
+                let mut line = format!("{} {}:{}", record.target(), record.file(), record.line());
+                if !record.span().is_empty() {
+                    line.push_str(&format!(" [{}]", record.span()));
+                }
+                line.push_str(&format!(" {}", record.message()));
+                if !record.fields().is_empty() {
+                    line.push_str(&format!(" {}", crate::kv::render(record.fields())));
+                }
+                captured.lock().unwrap().push(line);
+            }
+            return String::new();
+        }));
+
+        log();
+        set_logger(&previous);
+        return seen.lock().unwrap().clone();
+    }
+
     #[test]
     fn test_level_filtering() {
+        let _global = lock_global();
         let mut logger = Logger::new();
         logger.level(Level::Error); //Error as basis
 
@@ -1157,6 +2461,7 @@ mod tests {
 
     #[test]
     fn test_level_none() {
+        let _global = lock_global();
         let mut logger = Logger::new();
         logger.level(Level::None); //Set to None
 
@@ -1168,8 +2473,46 @@ mod tests {
         assert!(Level::Critical < logger.output_level);
     }
 
+    #[test]
+    fn test_ignore_mask() {
+        let mask = ignore_mask(&[Level::Trace, Level::Warning, Level::Diagnostic, Level::None]);
+
+        assert_eq!(mask[0], 1 | (1 << 60), "Trace and Warning should be the only low levels masked");
+        assert_eq!(mask[1], 0);
+        assert_eq!(mask[2], 0);
+        assert_eq!(mask[3], (1 << (245 % 64)) | (1 << (255 % 64)));
+    }
+
+    #[test]
+    fn test_custom_levels() {
+        let security = Level::register(115, "SECURITY", vec![Style::FGPurple]);
+
+        assert_eq!(security, Level::Custom(115));
+        assert!(Level::AUDIT < security && security < Level::FATAL);
+        assert!(Level::Info < Level::NOTICE && Level::NOTICE < Level::Warning);
+        assert!(Level::Critical < Level::AUDIT && Level::FATAL < Level::Diagnostic);
+        assert_eq!(s!(security), "SECURITY");
+        assert_eq!(s!(Level::AUDIT), "AUDIT");
+        assert_eq!(s!(Level::Custom(116)), "LEVEL116");
+        assert_eq!(Logger::new().styles(security), vec![Style::FGPurple]);
+        assert_eq!(Logger::new().styles(Level::FATAL), vec![Style::Bold, Style::BGRed]);
+        assert_eq!("fatal".parse::<Level>().unwrap(), Level::FATAL);
+
+        // Built-in severities are reserved, and a custom level given one is that built-in level
+        assert_eq!(Level::register(40, "NOTICE", vec![]), Level::Info);
+        assert_eq!(s!(Level::Custom(40)), "INFO");
+        assert_eq!(Level::Custom(40), Level::Info);
+        assert_eq!(Logger::new().styles(Level::Custom(40)), vec![Style::FGGreen]);
+
+        let mut logger = Logger::new();
+        logger.ignore(Level::Custom(40));
+        assert!(!logger.accepts(&Level::Info) && logger.accepts(&Level::NOTICE));
+        assert_eq!(ignore_mask(&logger.ignore), ignore_mask(&[Level::Info]));
+    }
+
     #[test]
     fn test_log_format() {
+        let _global = lock_global();
         let mut logger = Logger::new();
         logger.log_format("{level} - {message}");
 
@@ -1181,8 +2524,152 @@ mod tests {
         assert_eq!(formatted_message, "INFO - Test message");
     }
 
+    #[test]
+    fn test_format_record() {
+        let mut logger = Logger::new();
+        logger.log_format = s!("{level} {module_path} {span}: {message}");
+
+        let fields = [("status", Value::Display(&200))];
+        let mut record = Record::new(Level::Info, "app::http", format_args!("request served")).with_fields(&fields);
+        record.message = s!(record.args);
+        record.span = s!("handle");
+        record.causes = vec![s!("connection reset")];
+
+        assert_eq!(
+            format(&logger, &record),
+            "{level} app::http handle: request served status=200\n  caused by: connection reset"
+        );
+
+        // Empty placeholders leave no double spaces behind
+        logger.log_format = s!("[{level} {module_path}] {context} {message} {fields}");
+        record.context = vec![];
+        record.fields = &[];
+        record.causes = vec![];
+        assert_eq!(format(&logger, &record), "[{level} app::http] request served");
+
+        record.context = vec![(s!("request_id"), s!("7"))];
+        assert_eq!(format(&logger, &record), "[{level} app::http] request_id=7 request served");
+    }
+
+    // `warning!` is compiled out when the maximum level is above it
+    #[cfg(not(any(
+        feature = "max_level_off",
+        feature = "max_level_critical",
+        feature = "max_level_error",
+        feature = "release_max_level_off",
+        feature = "release_max_level_critical",
+        feature = "release_max_level_error"
+    )))]
+    #[test]
+    fn test_standalone_logger() {
+        let seen = Arc::new(std::sync::Mutex::new(vec![]));
+        let captured = seen.clone();
+        let logger = Logger::standalone().level(Level::Warning).filter(move |record| {
+            captured.lock().unwrap().push(format!("{} {}", record.target(), record.message()));
+            false
+        });
+
+        crate::info!(logger: &logger, "below the level");
+        crate::warning!(logger: &logger, target: "audit", "user {} deleted", 42);
+
+        assert!(logger.standalone);
+        assert_eq!(*seen.lock().unwrap(), vec![s!("audit user 42 deleted")]);
+    }
+
+    #[test]
+    fn test_static_output_level() {
+        let logger = Logger::standalone().terminal(false);
+        let evaluated = std::cell::RefCell::new(vec![]);
+        let evaluate = |level: Level| {
+            evaluated.borrow_mut().push(level.clone());
+            level
+        };
+
+        // Arguments of levels compiled out by the `max_level_*` features are never evaluated
+        crate::trace!(logger: &logger, "{}", evaluate(Level::Trace));
+        crate::info!(logger: &logger, "{}", evaluate(Level::Info));
+        crate::critical!(logger: &logger, "{}", evaluate(Level::Critical));
+
+        let expected: Vec<Level> = [Level::Trace, Level::Info, Level::Critical]
+            .into_iter()
+            .filter(|level| *level >= STATIC_OUTPUT_LEVEL)
+            .collect();
+        assert_eq!(*evaluated.borrow(), expected);
+    }
+
+    #[test]
+    fn test_backtrace_capture() {
+        let seen = Arc::new(std::sync::Mutex::new(vec![]));
+        let captured = seen.clone();
+        let logger = Logger::standalone()
+            .backtrace(Level::Trace)
+            .rate_limit(RateLimit::new(0, 1))
+            .formatter(move |record| {
+                captured.lock().unwrap().push((record.level.clone(), record.backtrace.is_some()));
+                String::new()
+            });
+
+        // Only the message getting past the rate limit pays for a backtrace, and diagnostics never do
+        for _ in 0..3 {
+            logger.log(Record::new(Level::Info, "app", format_args!("flood")));
+        }
+        logger.log(Record::new(Level::Diagnostic, "app", format_args!("state")));
+
+        assert_eq!(*seen.lock().unwrap(), vec![(Level::Info, true), (Level::Diagnostic, false)]);
+    }
+
+    #[test]
+    fn test_loggers_collapse_independently() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let capture = |name: &'static str| {
+            let seen = seen.clone();
+            return Logger::standalone().terminal_collapse(true).formatter(move |record| {
+                seen.lock().unwrap().push(format!("{} {}", name, record.message()));
+                String::new()
+            });
+        };
+        let first = capture("first");
+        let second = capture("second");
+
+        for _ in 0..3 {
+            first.log(Record::new(Level::Info, "app", format_args!("poll")));
+            second.log(Record::new(Level::Info, "app", format_args!("poll")));
+        }
+        first.flush().unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![s!("first poll"), s!("second poll"), s!("first last message repeated 2 times")]
+        );
+    }
+
+    #[test]
+    fn test_collapse_timeout_writes_summary() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let captured = seen.clone();
+        let logger = Logger::standalone()
+            .terminal_collapse(true)
+            .collapse_timeout(Duration::from_millis(50))
+            .formatter(move |record| {
+                captured.lock().unwrap().push(s!(record.message()));
+                return String::new();
+            });
+
+        for _ in 0..3 {
+            logger.log(Record::new(Level::Info, "app", format_args!("poll")));
+        }
+
+        // Nothing else is logged and nothing is flushed, yet the summary follows once the timeout expires
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while seen.lock().unwrap().len() < 2 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(*seen.lock().unwrap(), vec![s!("poll"), s!("last message repeated 2 times")]);
+    }
+
     #[test]
     fn test_output_enablement() {
+        let _global = lock_global();
         let mut logger = Logger::new();
 
         // Initially, terminal output is enabled, and file output is disabled.
@@ -1203,8 +2690,25 @@ mod tests {
         assert!(!logger.terminal_output, "Terminal output was not disabled");
     }
 
+    #[test]
+    fn test_collapse_enablement() {
+        let _global = lock_global();
+        let mut logger = Logger::new();
+
+        assert!(!logger.file_collapse, "File collapsing should be disabled by default");
+        assert!(!logger.terminal_collapse, "Terminal collapsing should be disabled by default");
+
+        logger.file_collapse(true);
+        logger.collapse_timeout(Duration::from_secs(5));
+
+        assert!(logger.file_collapse, "File collapsing was not enabled");
+        assert!(!logger.terminal_collapse, "Terminal collapsing should be set independently");
+        assert_eq!(logger.collapse_timeout, Duration::from_secs(5));
+    }
+
     #[test]
     fn test_ignore_levels() {
+        let _global = lock_global();
         let mut logger = Logger::new();
         logger.ignore(Level::Debug);
         logger.ignore(Level::Warning);
@@ -1225,6 +2729,7 @@ mod tests {
 
     #[test]
     fn test_style_assignment() {
+        let _global = lock_global();
         let mut logger = Logger::new();
         logger.style(Level::Info, vec![Style::FGGreen, Style::Bold]);
 
@@ -0,0 +1,414 @@
+use crate::{
+    error::*,
+    kv::Field,
+    logger::{self, Level, Logger},
+    record::Record,
+};
+use lazy_static::lazy_static;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, AtomicU8, Ordering},
+        Arc, RwLock,
+    },
+};
+
+lazy_static! {
+    static ref OVERRIDES: RwLock<HashMap<String, Overrides>> = RwLock::new(HashMap::new());
+    static ref CACHES: RwLock<HashMap<String, Arc<Cache>>> = RwLock::new(HashMap::new());
+}
+
+// Bumped whenever the global logger or any override changes, invalidating every resolved configuration
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// The generation of a cache which has never been resolved
+const UNRESOLVED: u64 = u64::MAX;
+
+/// The resolved configuration of a named logger, shared by every handle with the same name.
+///
+/// The output level is mirrored in an atomic, so messages below it are dropped without taking any lock.
+#[derive(Debug)]
+struct Cache {
+    generation: AtomicU64,
+    level: AtomicU8,
+    logger: RwLock<Option<Arc<Logger>>>,
+}
+
+impl Cache {
+    fn new() -> Self {
+        return Self {
+            generation: AtomicU64::new(UNRESOLVED),
+            level: AtomicU8::new(0),
+            logger: RwLock::new(None),
+        };
+    }
+
+    /// Checks a level against the cached output level - stale caches accept everything until they are resolved.
+    fn accepts(&self, level: &Level) -> bool {
+        if self.generation.load(Ordering::Acquire) != GENERATION.load(Ordering::Acquire) {
+            return true;
+        }
+        return level.severity() >= self.level.load(Ordering::Relaxed);
+    }
+}
+
+/// The settings a named logger overrides on top of its parent's configuration.
+#[derive(Clone, Debug, Default)]
+struct Overrides {
+    output_level: Option<Level>,
+    path: Option<PathBuf>,
+    file_output: Option<bool>,
+    terminal_output: Option<bool>,
+    log_format: Option<String>,
+    timestamp_format: Option<String>,
+}
+
+impl Overrides {
+    fn apply(&self, logger: &mut Logger) {
+        if let Some(level) = &self.output_level {
+            logger.output_level = level.clone();
+        }
+        if let Some(path) = &self.path {
+            logger.path = Some(path.clone());
+        }
+        if let Some(value) = self.file_output {
+            logger.file_output = value;
+        }
+        if let Some(value) = self.terminal_output {
+            logger.terminal_output = value;
+        }
+        if let Some(format) = &self.log_format {
+            logger.log_format = format.clone();
+        }
+        if let Some(format) = &self.timestamp_format {
+            logger.timestamp_format = format.clone();
+        }
+    }
+}
+
+/// Returns the named logger for a dot separated name such as `app.db.pool`, creating it on first use.
+///
+/// Named loggers form a hierarchy: `app.db.pool` inherits its level, outputs and formats from `app.db`, which
+/// inherits from `app`, which inherits from the global logger. Settings changed on a named logger override the
+/// inherited ones for it and its descendants, and changes anywhere up the hierarchy are picked up immediately.
+///
+/// # Arguments
+/// * `name` - The dot separated name of the logger.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let _app = logfather::get("app").log_format("[{timestamp} {level} {target}] {message}");
+/// let pool = logfather::get("app.db.pool").level(Level::Warning); // Inherits the format from `app`
+///
+/// info!(logger: &pool, "connection opened"); // Below the pool's level
+/// warning!(logger: &pool, "pool exhausted"); // [... WARNING app.db.pool] pool exhausted
+/// ```
+pub fn get(name: &str) -> NamedLogger {
+    // Only the first lookup of a name has to take the write lock
+    let cached = CACHES.read().ok().and_then(|caches| caches.get(name).cloned());
+    let cache = match cached {
+        Some(cache) => cache,
+        None => match CACHES.write() {
+            Ok(mut caches) => caches.entry(name.to_string()).or_insert_with(|| Arc::new(Cache::new())).clone(),
+            Err(_) => Arc::new(Cache::new()),
+        },
+    };
+
+    return NamedLogger {
+        name: name.to_string(),
+        cache,
+    };
+}
+
+/// Marks every resolved named logger configuration as stale.
+pub(crate) fn invalidate() {
+    GENERATION.fetch_add(1, Ordering::Release);
+}
+
+/// A handle to a logger in the named hierarchy, obtained with `logfather::get`.
+///
+/// Handles are cheap to clone and every handle with the same name shares the same configuration. Messages logged
+/// through a named logger use its name as their target unless one is given with `target:`. The configuration is
+/// resolved once and cached until the global logger or an override changes, and messages below the logger's level
+/// are dropped without taking any lock. Repeated and rate limited messages are tracked separately from the global
+/// logger's, so pending summaries are written with `NamedLogger::flush`.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let db = logfather::get("app.db").path("db.log").file(true);
+/// error!(logger: &db, "connection refused");
+/// ```
+#[derive(Clone, Debug)]
+pub struct NamedLogger {
+    name: String,
+    cache: Arc<Cache>,
+}
+
+impl PartialEq for NamedLogger {
+    fn eq(&self, other: &Self) -> bool {
+        return self.name == other.name;
+    }
+}
+
+impl Eq for NamedLogger {}
+
+impl NamedLogger {
+    /// Returns the dot separated name of the logger.
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    /// Returns the logger this one inherits from, or `None` for a top level logger.
+    pub fn parent(&self) -> Option<NamedLogger> {
+        return self.name.rsplit_once('.').map(|(parent, _)| get(parent));
+    }
+
+    /// Overrides the minimum level of log messages for this logger and its descendants.
+    ///
+    /// # Arguments
+    /// * `level` - The minimum `Level` of log messages to output.
+    pub fn level(&mut self, level: Level) -> Self {
+        self.update(|overrides| overrides.output_level = Some(level));
+        return self.to_owned();
+    }
+
+    /// Overrides the file path for this logger and its descendants.
+    ///
+    /// # Arguments
+    /// * `path` - A string slice that holds the path to the log file.
+    pub fn path(&mut self, path: &str) -> Self {
+        self.update(|overrides| overrides.path = Some(PathBuf::from(path)));
+        return self.to_owned();
+    }
+
+    /// Enables or disables file output for this logger and its descendants.
+    ///
+    /// # Arguments
+    /// * `value` - A boolean value where `true` enables file output and `false` disables it.
+    pub fn file(&mut self, value: bool) -> Self {
+        self.update(|overrides| overrides.file_output = Some(value));
+        return self.to_owned();
+    }
+
+    /// Enables or disables terminal output for this logger and its descendants.
+    ///
+    /// # Arguments
+    /// * `value` - A boolean value where `true` enables terminal output and `false` disables it.
+    pub fn terminal(&mut self, value: bool) -> Self {
+        self.update(|overrides| overrides.terminal_output = Some(value));
+        return self.to_owned();
+    }
+
+    /// Overrides the format string for log messages for this logger and its descendants.
+    ///
+    /// # Arguments
+    /// * `format` - A string slice representing the log message format.
+    pub fn log_format(&mut self, format: &str) -> Self {
+        self.update(|overrides| overrides.log_format = Some(format.to_string()));
+        return self.to_owned();
+    }
+
+    /// Overrides the format string for timestamps for this logger and its descendants.
+    ///
+    /// # Arguments
+    /// * `format` - A string slice representing the timestamp format.
+    pub fn timestamp_format(&mut self, format: &str) -> Self {
+        self.update(|overrides| overrides.timestamp_format = Some(format.to_string()));
+        return self.to_owned();
+    }
+
+    /// Returns the effective configuration of the logger after inheriting from its ancestors.
+    ///
+    /// The returned `Logger` is standalone, so changing it affects neither this logger nor the global one.
+    pub fn config(&self) -> Result<Logger, LogfatherError> {
+        return Ok(Logger::clone(&*self.resolved()?));
+    }
+
+    /// Returns the cached configuration, resolving it again if the hierarchy changed since it was cached.
+    fn resolved(&self) -> Result<Arc<Logger>, LogfatherError> {
+        let generation = GENERATION.load(Ordering::Acquire);
+        let previous = self.cache.logger.read().map_err(access_error)?.clone();
+        if let (Some(logger), true) = (&previous, self.cache.generation.load(Ordering::Acquire) == generation) {
+            return Ok(logger.clone());
+        }
+
+        let mut logger = logger::global()?;
+        logger.standalone = true;
+        {
+            let overrides = OVERRIDES.read().map_err(access_error)?;
+            for ancestor in ancestors(&self.name) {
+                if let Some(overrides) = overrides.get(ancestor) {
+                    overrides.apply(&mut logger);
+                }
+            }
+        }
+
+        // Pending summaries survive the change, while the global logger's are left to it
+        match previous {
+            Some(previous) => {
+                logger.collapser = previous.collapser.clone();
+                logger.limiter = previous.limiter.clone();
+            }
+            None => {
+                let fresh = Logger::new();
+                logger.collapser = fresh.collapser;
+                logger.limiter = fresh.limiter;
+            }
+        }
+
+        // Tagged with the generation read up front, so a change made meanwhile is picked up on the next call
+        let logger = Arc::new(logger);
+        *self.cache.logger.write().map_err(access_error)? = Some(logger.clone());
+        self.cache.level.store(logger.output_level.severity(), Ordering::Relaxed);
+        self.cache.generation.store(generation, Ordering::Release);
+        return Ok(logger);
+    }
+
+    /// Logs a prepared `Record` through this logger.
+    ///
+    /// # Arguments
+    /// * `record` - The log message to write.
+    pub fn log(&self, record: Record) {
+        if !self.cache.accepts(&record.level) {
+            return;
+        }
+        self.resolved().expect("Could not read logger").log(record);
+    }
+
+    /// Logs a prepared `Record` through this logger.
+    /// - Outputs a `LogfatherResult` in the event of failure instead of console outputs or panics.
+    ///
+    /// # Arguments
+    /// * `record` - The log message to write.
+    pub fn result_log(&self, record: Record) -> LogfatherResult {
+        if !self.cache.accepts(&record.level) {
+            return Ok(());
+        }
+        return self.resolved()?.result_log(record);
+    }
+
+    /// Writes out any summaries of repeated or rate limited messages that are still pending in this logger.
+    pub fn flush(&self) -> LogfatherResult {
+        return self.resolved()?.flush();
+    }
+
+    /// Logs a message from a logging macro through this logger.
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn log_callsite(
+        &self,
+        level: Level,
+        module_path: &str,
+        target: Option<&str>,
+        args: std::fmt::Arguments,
+        fields: &[Field],
+        error: Option<&dyn std::error::Error>,
+    ) {
+        //Skip building the record when the level is filtered out
+        if !self.cache.accepts(&level) {
+            return;
+        }
+
+        let mut record = Record::new(level, module_path, args).with_fields(fields);
+        record.target = target.unwrap_or(&self.name);
+        record.error = error;
+        self.log(record);
+    }
+
+    /// Logs a message from a `r_` logging macro through this logger.
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn result_log_callsite(
+        &self,
+        level: Level,
+        module_path: &str,
+        target: Option<&str>,
+        args: std::fmt::Arguments,
+        fields: &[Field],
+        error: Option<&dyn std::error::Error>,
+    ) -> LogfatherResult {
+        //Skip building the record when the level is filtered out
+        if !self.cache.accepts(&level) {
+            return Ok(());
+        }
+
+        let mut record = Record::new(level, module_path, args).with_fields(fields);
+        record.target = target.unwrap_or(&self.name);
+        record.error = error;
+        return self.result_log(record);
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Overrides)) {
+        if let Ok(mut overrides) = OVERRIDES.write() {
+            change(overrides.entry(self.name.clone()).or_default());
+        }
+        invalidate();
+    }
+}
+
+/// Lists a name and its ancestors, outermost first - `app.db` yields `app` then `app.db`.
+fn ancestors(name: &str) -> impl Iterator<Item = &str> {
+    return name
+        .match_indices('.')
+        .map(move |(index, _)| &name[..index])
+        .chain(std::iter::once(name));
+}
+
+fn access_error(error: impl std::fmt::Display) -> LogfatherError {
+    return LogfatherError::LoggerAccessError(error.to_string());
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ancestors() {
+        assert_eq!(ancestors("app.db.pool").collect::<Vec<&str>>(), vec!["app", "app.db", "app.db.pool"]);
+        assert_eq!(ancestors("app").collect::<Vec<&str>>(), vec!["app"]);
+    }
+
+    #[test]
+    fn test_inherits_and_overrides() {
+        let _global = crate::logger::tests::lock_global();
+        get("inherit").log_format("{level} {message}").level(Level::Error);
+        let pool = get("inherit.db.pool").level(Level::Warning);
+
+        let config = pool.config().unwrap();
+        assert_eq!(config.log_format, "{level} {message}");
+        assert_eq!(config.output_level, Level::Warning);
+        assert!(config.standalone);
+
+        // Parent changes reach cached descendants
+        get("inherit.db").log_format("{message}");
+        assert_eq!(pool.config().unwrap().log_format, "{message}");
+        assert_eq!(pool.parent(), Some(get("inherit.db")));
+    }
+
+    #[test]
+    fn test_cached_level() {
+        // Replacing the global logger makes every cache stale, so no other test may do so meanwhile
+        let _global = crate::logger::tests::lock_global();
+        let quiet = get("cached").level(Level::Error);
+        let handle = get("cached");
+        assert!(Arc::ptr_eq(&quiet.cache, &handle.cache));
+
+        // Once resolved, levels below the logger's are skipped without locking
+        handle.resolved().unwrap();
+        assert!(!handle.cache.accepts(&Level::Warning));
+        assert!(handle.cache.accepts(&Level::Critical));
+
+        // Changes make the cache stale
+        get("cached").level(Level::Trace);
+        assert!(handle.cache.accepts(&Level::Debug));
+    }
+}
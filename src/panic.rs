@@ -0,0 +1,103 @@
+use crate::{
+    error::LogfatherResult,
+    logger::{self, Level, Logger},
+    record::Record,
+};
+use std::{any::Any, panic::Location};
+
+/// Installs a panic hook which logs panics through logfather before running the previously installed hook.
+///
+/// Panics are logged at `Critical` to every configured output with the thread name, location and panic message, plus
+/// a backtrace when the logger's `backtrace` level covers `Critical`. Like `Diagnostic` messages, they skip the
+/// logger's filters, sampling and rate limiting. Buffered outputs are flushed before chaining to the previous hook,
+/// which by default still prints the panic to stderr - and which runs alone when the logger is locked or poisoned.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let logger = Logger::new().file(true).path("log.txt").backtrace(Level::Critical);
+/// logfather::install_panic_hook();
+///
+/// panic!("out of cheese"); // [... CRITICAL panic] thread 'main' panicked at src/main.rs:7:1: out of cheese
+/// ```
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        //Waiting on a logger held by the panicking thread would hang it, so only log when the lock is free
+        if let Some(logger) = logger::try_global() {
+            let thread = std::thread::current();
+            let message = panic_message(thread.name(), info.location(), info.payload());
+
+            if let Err(e) = forward(&logger, info.location(), format_args!("{}", message)).and_then(|_| logger.flush()) {
+                eprintln!("{e}");
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// Logs the panic message at the panic's location, taking the message as `Arguments` so it can be built inline.
+fn forward(logger: &Logger, location: Option<&Location>, args: std::fmt::Arguments) -> LogfatherResult {
+    let mut record = Record::new(Level::Critical, "panic", args);
+    if let Some(location) = location {
+        record = record.with_location(location.file(), location.line());
+        record.column = location.column();
+    }
+    return logger.dispatch_unfiltered(record);
+}
+
+/// Renders a panic the way the standard library's hook does - `thread 'main' panicked at src/main.rs:7:1: message`.
+fn panic_message(thread: Option<&str>, location: Option<&Location>, payload: &(dyn Any + Send)) -> String {
+    let payload = if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "Box<dyn Any>"
+    };
+
+    return match location {
+        Some(location) => format!("thread '{}' panicked at {}: {}", thread.unwrap_or("<unnamed>"), location, payload),
+        None => format!("thread '{}' panicked: {}", thread.unwrap_or("<unnamed>"), payload),
+    };
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SampleRate, SampleRule};
+
+    #[test]
+    fn test_panic_message() {
+        let location = Location::caller();
+        let message = panic_message(Some("worker"), Some(location), &"out of cheese");
+        assert_eq!(message, format!("thread 'worker' panicked at {}: out of cheese", location));
+
+        let message = panic_message(None, None, &String::from("out of cheese"));
+        assert_eq!(message, "thread '<unnamed>' panicked: out of cheese");
+
+        assert_eq!(panic_message(None, None, &42), "thread '<unnamed>' panicked: Box<dyn Any>");
+    }
+
+    #[test]
+    fn test_panics_skip_filters() {
+        let path = std::env::temp_dir().join(format!("logfather-panic-{}.log", std::process::id()));
+        let logger = Logger::standalone()
+            .terminal(false)
+            .file(true)
+            .path(path.to_str().unwrap())
+            .filter(|_| false)
+            .sample(SampleRule::new(SampleRate::Percent(0.0)));
+
+        forward(&logger, Some(Location::caller()), format_args!("out of cheese")).unwrap();
+        logger.log(Record::new(Level::Critical, "panic", format_args!("filtered out")));
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(text.contains("out of cheese") && !text.contains("filtered out"), "{text}");
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{error::*, logger::Level, writer::LogWriter};
+use std::{
+    io::Read,
+    panic::Location,
+    path::Path,
+    process::{Command, ExitStatus, Stdio},
+    thread::JoinHandle,
+};
+
+/// Runs a command, logging its stdout and stderr line by line, and returns its exit status.
+///
+/// Shorthand for `ChildOutput::new().run(command)`, which logs stdout at `Info` and stderr at `Error`.
+///
+/// # Arguments
+/// * `command` - The command to spawn - its stdout and stderr are replaced with pipes.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+/// use std::process::Command;
+///
+/// let status = logfather::process::run(Command::new("ffmpeg").arg("-version")).expect("Could not run ffmpeg");
+/// ```
+pub fn run(command: &mut Command) -> Result<ExitStatus, LogfatherError> {
+    return ChildOutput::new().run(command);
+}
+
+/// Levels at which the output of a child process is logged.
+///
+/// Every line the child writes is logged with the module path `child` and the target `child:<program>[<pid>]`, such
+/// as `child:ffmpeg[4242]`, so its output can be filtered, sampled and rate limited like any other subsystem.
+///
+/// # Fields
+/// - `stdout`: Level of lines written to the child's stdout - `Info` by default.
+/// - `stderr`: Level of lines written to the child's stderr - `Error` by default.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+/// use std::process::Command;
+///
+/// let status = ChildOutput::new()
+///     .stdout(Level::Debug)
+///     .stderr(Level::Warning)
+///     .run(Command::new("ffmpeg").args(["-i", "in.mp4", "out.webm"]))
+///     .expect("Could not run ffmpeg"); // [... WARNING child:ffmpeg[4242]] frame= 120 fps= 30 ...
+///
+/// if !status.success() {
+///     error!("transcoding failed with {status}");
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChildOutput {
+    pub(crate) stdout: Level,
+    pub(crate) stderr: Level,
+}
+
+impl Default for ChildOutput {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl ChildOutput {
+    /// Constructs a new `ChildOutput` logging stdout at `Info` and stderr at `Error`.
+    pub fn new() -> Self {
+        return Self { stdout: Level::Info, stderr: Level::Error };
+    }
+
+    /// Sets the level of lines written to the child's stdout.
+    ///
+    /// # Arguments
+    /// * `level` - The `Level` stdout lines are logged at.
+    pub fn stdout(mut self, level: Level) -> Self {
+        self.stdout = level;
+        return self;
+    }
+
+    /// Sets the level of lines written to the child's stderr.
+    ///
+    /// # Arguments
+    /// * `level` - The `Level` stderr lines are logged at.
+    pub fn stderr(mut self, level: Level) -> Self {
+        self.stderr = level;
+        return self;
+    }
+
+    /// Spawns a command and logs its output from background threads until it exits, then returns its exit status.
+    ///
+    /// # Arguments
+    /// * `command` - The command to spawn - its stdout and stderr are replaced with pipes.
+    pub fn run(&self, command: &mut Command) -> Result<ExitStatus, LogfatherError> {
+        let mut child = command.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+        let program = Path::new(command.get_program())
+            .file_name()
+            .unwrap_or(command.get_program())
+            .to_string_lossy()
+            .into_owned();
+        let target = format!("child:{}[{}]", program, child.id());
+
+        let readers = [
+            child.stdout.take().map(|stdout| read_lines(stdout, self.stdout.clone(), target.clone())),
+            child.stderr.take().map(|stderr| read_lines(stderr, self.stderr.clone(), target.clone())),
+        ];
+
+        let status = child.wait()?;
+        for reader in readers.into_iter().flatten() {
+            reader
+                .join()
+                .map_err(|_| LogfatherError::LoggerAccessError(format!("the output reader of {target} panicked")))??;
+        }
+        return Ok(status);
+    }
+}
+
+/// Logs every line read from a child's pipe on a background thread until the pipe closes.
+///
+/// Lines are located at the caller, so stdout and stderr are rate limited apart.
+#[track_caller]
+fn read_lines(mut pipe: impl Read + Send + 'static, level: Level, target: String) -> JoinHandle<Result<(), LogfatherError>> {
+    let location = Location::caller();
+    return std::thread::spawn(move || {
+        std::io::copy(&mut pipe, &mut LogWriter::new(level, &target).module_path("child").location(location))?;
+        return Ok(());
+    });
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_returns_exit_status() {
+        let status = ChildOutput::new()
+            .stdout(Level::Trace)
+            .stderr(Level::Trace)
+            .run(Command::new("sh").args(["-c", "echo out; echo err >&2; exit 3"]))
+            .unwrap();
+        assert_eq!(status.code(), Some(3));
+
+        assert!(run(&mut Command::new("logfather-missing-binary")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lines_are_logged_per_child() {
+        let seen = crate::logger::tests::capture("child:sh[", || {
+            ChildOutput::new()
+                .stdout(Level::Trace)
+                .stderr(Level::Trace)
+                .run(Command::new("sh").args(["-c", "echo captured-out; echo captured-err >&2"]))
+                .unwrap();
+        });
+
+        // Other tests may run `sh` meanwhile
+        let seen: Vec<&String> = seen.iter().filter(|line| line.contains(" captured-")).collect();
+        assert_eq!(seen.len(), 2, "{seen:?}");
+        let (target, _) = seen[0].split_once(' ').unwrap();
+        let pid = target.strip_prefix("child:sh[").and_then(|rest| rest.strip_suffix(']')).unwrap();
+        assert!(pid.parse::<u32>().is_ok(), "{target}");
+
+        let mut messages: Vec<&str> = seen.iter().map(|line| line.rsplit(' ').next().unwrap()).collect();
+        messages.sort();
+        assert_eq!(messages, vec!["captured-err", "captured-out"]);
+        assert!(seen.iter().all(|line| line.starts_with(target)), "{seen:?}");
+    }
+}
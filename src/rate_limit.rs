@@ -0,0 +1,328 @@
+use crate::{logger::Level, record::Record};
+use std::{collections::HashMap, sync::Mutex, time::Instant};
+
+/// Number of buckets after which full, idle buckets are evicted - and beyond which new keys go unlimited.
+const MAX_BUCKETS: usize = 4096;
+
+/// Determines what groups log messages together for rate limiting.
+///
+/// # Variants
+///
+/// - `Callsite`: Every callsite (target, file, line and column) has its own bucket, so writers and child processes
+///   sharing a line of code are still limited apart (default).
+/// - `Message`: Every distinct formatted message has its own bucket, regardless of where it was logged from.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let limit = RateLimit::new(10, 50).key(RateLimitKey::Message);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum RateLimitKey {
+    Callsite,
+    Message,
+}
+
+/// Token bucket configuration used to limit how often a single callsite (or message) may log.
+///
+/// Each bucket holds up to `burst` tokens and is refilled at `per_second` tokens per second. Every log
+/// message consumes one token; messages arriving at an empty bucket are suppressed and counted. Once the
+/// bucket has refilled, the next message is preceded by a summary line such as
+/// `suppressed 12,345 similar messages from app::net:88`. The summary is only written once the same bucket logs
+/// again, so when a flood ends and its callsite goes quiet, it stays pending until `flush` is called - no timer
+/// writes it.
+///
+/// # Fields
+/// - `per_second`: Number of tokens restored to the bucket every second.
+/// - `burst`: Maximum number of tokens the bucket can hold.
+/// - `key`: How log messages are grouped into buckets.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let mut logger = Logger::new();
+/// logger.rate_limit(RateLimit::new(5, 20)); // 5 messages per second with bursts of up to 20 per callsite
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(deny_unknown_fields))]
+pub struct RateLimit {
+    pub(crate) per_second: f64,
+    pub(crate) burst: f64,
+    pub(crate) key: RateLimitKey,
+}
+
+impl RateLimit {
+    /// Constructs a new `RateLimit` keyed by callsite.
+    ///
+    /// # Arguments
+    /// * `per_second` - The sustained number of messages allowed per second.
+    /// * `burst` - The number of messages allowed in a single burst - values below 1 are treated as 1.
+    pub fn new(per_second: u32, burst: u32) -> Self {
+        return Self {
+            per_second: per_second as f64,
+            burst: burst.max(1) as f64,
+            key: RateLimitKey::Callsite,
+        };
+    }
+
+    /// Sets how log messages are grouped into buckets.
+    ///
+    /// # Arguments
+    /// * `key` - The `RateLimitKey` used to group messages.
+    pub fn key(mut self, key: RateLimitKey) -> Self {
+        self.key = key;
+        return self;
+    }
+}
+
+/// The outcome of passing a message through the rate limiter.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Verdict {
+    /// The message should be logged - holds the number of messages suppressed since the last one was logged.
+    Emit(u64),
+    /// The message should be dropped.
+    Suppress,
+}
+
+/// Messages suppressed by a bucket which still need their summary line written.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Suppressed {
+    pub(crate) level: Level,
+    pub(crate) target: String,
+    pub(crate) line: u32,
+    pub(crate) count: u64,
+}
+
+impl Suppressed {
+    /// The summary line written in place of the suppressed messages.
+    pub(crate) fn message(&self) -> String {
+        return format!(
+            "suppressed {} similar messages from {}:{}",
+            separate_thousands(self.count),
+            self.target,
+            self.line
+        );
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+    suppressed: u64,
+    level: Level,
+    target: String,
+    line: u32,
+}
+
+impl Bucket {
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.per_second).min(limit.burst);
+        self.last = now;
+    }
+}
+
+/// The token buckets of a logger.
+#[derive(Debug, Default)]
+pub(crate) struct Limiter {
+    buckets: HashMap<String, Bucket>,
+}
+
+impl Limiter {
+    fn check(&mut self, limit: &RateLimit, record: &Record, now: Instant) -> Verdict {
+        let key = match limit.key {
+            RateLimitKey::Callsite => format!("{}@{}:{}:{}", record.target, record.file, record.line, record.column),
+            RateLimitKey::Message => record.message.clone(),
+        };
+
+        if !self.buckets.contains_key(&key) && self.buckets.len() >= MAX_BUCKETS {
+            self.evict(limit, now);
+            // Every bucket is busy, so the new key can't be tracked
+            if self.buckets.len() >= MAX_BUCKETS {
+                return Verdict::Emit(0);
+            }
+        }
+
+        let bucket = self.buckets.entry(key).or_insert_with(|| Bucket {
+            tokens: limit.burst,
+            last: now,
+            suppressed: 0,
+            level: record.level.clone(),
+            target: record.target.to_string(),
+            line: record.line,
+        });
+
+        // Refill based on the time elapsed since the last message
+        bucket.refill(limit, now);
+
+        if bucket.tokens < 1.0 {
+            bucket.suppressed += 1;
+            return Verdict::Suppress;
+        }
+
+        bucket.tokens -= 1.0;
+        return Verdict::Emit(std::mem::take(&mut bucket.suppressed));
+    }
+
+    /// Removes the buckets that have refilled completely and have no summary pending - they behave like new ones.
+    fn evict(&mut self, limit: &RateLimit, now: Instant) {
+        self.buckets.retain(|_, bucket| {
+            bucket.refill(limit, now);
+            bucket.tokens < limit.burst || bucket.suppressed > 0
+        });
+    }
+
+    fn drain(&mut self) -> Vec<Suppressed> {
+        return self
+            .buckets
+            .values_mut()
+            .filter(|bucket| bucket.suppressed > 0)
+            .map(|bucket| Suppressed {
+                level: bucket.level.clone(),
+                target: bucket.target.clone(),
+                line: bucket.line,
+                count: std::mem::take(&mut bucket.suppressed),
+            })
+            .collect();
+    }
+}
+
+/// Passes a message through a logger's rate limiter.
+///
+/// # Arguments
+/// * `limiter` - The rate limiter of the logger writing the message.
+/// * `limit` - The active rate limit configuration.
+/// * `record` - The log message with its callsite and formatted message.
+pub(crate) fn check(limiter: &Mutex<Limiter>, limit: &RateLimit, record: &Record) -> Verdict {
+    return match limiter.lock() {
+        Ok(mut limiter) => limiter.check(limit, record, Instant::now()),
+        // Never drop messages because the limiter itself is unavailable
+        Err(_) => Verdict::Emit(0),
+    };
+}
+
+/// Takes the summaries of every bucket that suppressed messages since it last logged one.
+pub(crate) fn drain(limiter: &Mutex<Limiter>) -> Vec<Suppressed> {
+    return match limiter.lock() {
+        Ok(mut limiter) => limiter.drain(),
+        Err(_) => vec![],
+    };
+}
+
+/// Builds the line logged when a bucket lifts after suppressing messages.
+pub(crate) fn summary(suppressed: u64, record: &Record) -> String {
+    let suppressed = Suppressed {
+        level: record.level.clone(),
+        target: record.target.to_string(),
+        line: record.line,
+        count: suppressed,
+    };
+    return suppressed.message();
+}
+
+/// Formats a number with `,` as the thousands separator.
+fn separate_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let groups: Vec<&str> = digits
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|group| std::str::from_utf8(group).unwrap_or_default())
+        .collect();
+    return groups.join(",");
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bucket_suppresses_and_recovers() {
+        let limit = RateLimit::new(1, 2);
+        let mut limiter = Limiter::default();
+        let start = Instant::now();
+        let record = Record::new(Level::Info, "app", format_args!("flood"));
+
+        assert_eq!(limiter.check(&limit, &record, start), Verdict::Emit(0));
+        assert_eq!(limiter.check(&limit, &record, start), Verdict::Emit(0));
+        assert_eq!(limiter.check(&limit, &record, start), Verdict::Suppress);
+        assert_eq!(limiter.check(&limit, &record, start), Verdict::Suppress);
+
+        // One token is restored after a second and the suppressed count is reported
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.check(&limit, &record, later), Verdict::Emit(2));
+        assert_eq!(limiter.check(&limit, &record, later), Verdict::Suppress);
+    }
+
+    #[test]
+    fn test_buckets_are_independent() {
+        let limit = RateLimit::new(0, 1).key(RateLimitKey::Message);
+        let mut limiter = Limiter::default();
+        let now = Instant::now();
+        let mut record = Record::new(Level::Info, "app", format_args!(""));
+        record.message = String::from("first");
+
+        assert_eq!(limiter.check(&limit, &record, now), Verdict::Emit(0));
+        assert_eq!(limiter.check(&limit, &record, now), Verdict::Suppress);
+        record.message = String::from("second");
+        assert_eq!(limiter.check(&limit, &record, now), Verdict::Emit(0));
+    }
+
+    #[test]
+    fn test_idle_buckets_are_evicted() {
+        let limit = RateLimit::new(1, 1).key(RateLimitKey::Message);
+        let mut limiter = Limiter::default();
+        let start = Instant::now();
+        let mut record = Record::new(Level::Info, "app", format_args!(""));
+
+        for i in 0..MAX_BUCKETS {
+            record.message = i.to_string();
+            assert_eq!(limiter.check(&limit, &record, start), Verdict::Emit(0));
+        }
+        // Every bucket is still empty, so new messages pass untracked
+        record.message = String::from("new");
+        assert_eq!(limiter.check(&limit, &record, start), Verdict::Emit(0));
+        assert_eq!(limiter.buckets.len(), MAX_BUCKETS);
+
+        // Once they have refilled they are evicted to make room
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.check(&limit, &record, later), Verdict::Emit(0));
+        assert_eq!(limiter.buckets.len(), 1);
+    }
+
+    #[test]
+    fn test_drain_takes_pending_summaries() {
+        let limit = RateLimit::new(0, 1);
+        let mut limiter = Limiter::default();
+        let now = Instant::now();
+        let record = Record::new(Level::Warning, "app::net", format_args!("flood"));
+
+        limiter.check(&limit, &record, now);
+        limiter.check(&limit, &record, now);
+        limiter.check(&limit, &record, now);
+
+        let pending = limiter.drain();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].level, Level::Warning);
+        assert_eq!(pending[0].message(), format!("suppressed 2 similar messages from app::net:{}", record.line));
+        assert!(limiter.drain().is_empty());
+    }
+
+    #[test]
+    fn test_separate_thousands() {
+        assert_eq!(separate_thousands(0), "0");
+        assert_eq!(separate_thousands(999), "999");
+        assert_eq!(separate_thousands(1000), "1,000");
+        assert_eq!(separate_thousands(12345), "12,345");
+        assert_eq!(separate_thousands(1234567), "1,234,567");
+    }
+}
@@ -0,0 +1,286 @@
+use crate::{kv::Field, logger::Level};
+use chrono::{DateTime, Utc};
+use std::{backtrace::Backtrace, panic::Location, sync::Arc, thread::Thread};
+
+/// A single log message on its way through the logger's filters, formatter and outputs.
+///
+/// Records are created by the logging macros and functions, enriched with the thread's diagnostic context,
+/// span path, sampling rate and backtrace, and then handed to every filter, formatter and output. They are
+/// also the entry point for integrations forwarding messages from other logging systems through `log_record`.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// // Forward a message from another logging system
+/// let record = Record::new(Level::Warning, "vendor::db", format_args!("slow query"))
+///     .with_location("vendor/db.rs", 42);
+/// log_record(record);
+/// ```
+#[derive(Debug)]
+pub struct Record<'a> {
+    pub(crate) level: Level,
+    pub(crate) timestamp: DateTime<Utc>,
+    pub(crate) module_path: &'a str,
+    pub(crate) target: &'a str,
+    pub(crate) file: &'a str,
+    pub(crate) line: u32,
+    pub(crate) column: u32,
+    pub(crate) thread: Thread,
+    pub(crate) args: std::fmt::Arguments<'a>,
+    pub(crate) message: String,
+    pub(crate) fields: &'a [Field<'a>],
+    pub(crate) error: Option<&'a dyn std::error::Error>,
+    pub(crate) causes: Vec<String>,
+    pub(crate) context: Vec<(String, String)>,
+    pub(crate) span: String,
+    pub(crate) backtrace: Option<Backtrace>,
+    pub(crate) sample_rate: f64,
+}
+
+impl<'a> Record<'a> {
+    /// Constructs a new `Record` timestamped now, on the current thread, located at the caller.
+    ///
+    /// # Arguments
+    /// * `level` - The severity level of the message.
+    /// * `module_path` - The module path where the message originates.
+    /// * `args` - The message broken into fragments.
+    #[track_caller]
+    pub fn new(level: Level, module_path: &'a str, args: std::fmt::Arguments<'a>) -> Self {
+        let location = Location::caller();
+        return Self {
+            level,
+            timestamp: Utc::now(),
+            module_path,
+            target: module_path,
+            file: location.file(),
+            line: location.line(),
+            column: location.column(),
+            thread: std::thread::current(),
+            args,
+            message: String::new(),
+            fields: &[],
+            error: None,
+            causes: vec![],
+            context: vec![],
+            span: String::new(),
+            backtrace: None,
+            sample_rate: 1.0,
+        };
+    }
+
+    /// Sets the target the message is filtered, routed and displayed by - the module path by default.
+    ///
+    /// # Arguments
+    /// * `target` - A logical subsystem such as `audit` or `security`.
+    pub fn with_target(mut self, target: &'a str) -> Self {
+        self.target = target;
+        return self;
+    }
+
+    /// Sets the source location the message was logged from.
+    ///
+    /// # Arguments
+    /// * `file` - The source file of the callsite.
+    /// * `line` - The line of the callsite.
+    pub fn with_location(mut self, file: &'a str, line: u32) -> Self {
+        self.file = file;
+        self.line = line;
+        self.column = 0;
+        return self;
+    }
+
+    /// Sets the column of the source location, which `with_location` leaves at 0.
+    pub(crate) fn with_column(mut self, column: u32) -> Self {
+        self.column = column;
+        return self;
+    }
+
+    /// Attaches structured key-value fields to the message.
+    ///
+    /// # Arguments
+    /// * `fields` - The fields travelling with the message.
+    pub fn with_fields(mut self, fields: &'a [Field<'a>]) -> Self {
+        self.fields = fields;
+        return self;
+    }
+
+    /// Attaches an error to the message - its `source()` chain is rendered below the line.
+    ///
+    /// # Arguments
+    /// * `error` - The error being logged.
+    pub fn with_error(mut self, error: &'a dyn std::error::Error) -> Self {
+        self.error = Some(error);
+        return self;
+    }
+
+    /// The severity level of the message.
+    pub fn level(&self) -> &Level {
+        return &self.level;
+    }
+
+    /// The time the record was created, in UTC.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        return self.timestamp;
+    }
+
+    /// The module path where the message originates.
+    pub fn module_path(&self) -> &str {
+        return self.module_path;
+    }
+
+    /// The target the message is filtered, routed and displayed by - the module path unless overridden.
+    pub fn target(&self) -> &str {
+        return self.target;
+    }
+
+    /// The source file of the callsite.
+    pub fn file(&self) -> &str {
+        return self.file;
+    }
+
+    /// The line of the callsite.
+    pub fn line(&self) -> u32 {
+        return self.line;
+    }
+
+    /// The column of the callsite - `0` if the location was set with `with_location`.
+    pub fn column(&self) -> u32 {
+        return self.column;
+    }
+
+    /// The thread the message was logged on.
+    pub fn thread(&self) -> &Thread {
+        return &self.thread;
+    }
+
+    /// The message broken into fragments, as passed to the logging macro.
+    pub fn args(&self) -> std::fmt::Arguments<'a> {
+        return self.args;
+    }
+
+    /// The formatted message, followed by the attached error if there is one.
+    ///
+    /// Empty until the record has been accepted by the logger's level filters.
+    pub fn message(&self) -> &str {
+        return &self.message;
+    }
+
+    /// The structured key-value fields attached to the message.
+    pub fn fields(&self) -> &[Field<'a>] {
+        return self.fields;
+    }
+
+    /// The error attached to the message.
+    pub fn error(&self) -> Option<&dyn std::error::Error> {
+        return self.error;
+    }
+
+    /// The messages of the attached error's `source()` chain, outermost first.
+    pub fn causes(&self) -> &[String] {
+        return &self.causes;
+    }
+
+    /// The thread's diagnostic context at the time the message was logged.
+    pub fn context(&self) -> &[(String, String)] {
+        return &self.context;
+    }
+
+    /// The path of the spans open when the message was logged, separated by `/`.
+    pub fn span(&self) -> &str {
+        return &self.span;
+    }
+
+    /// The backtrace captured for the message, if its level called for one.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        return self.backtrace.as_ref();
+    }
+
+    /// The fraction of matching messages kept by sampling, from `0` to `1`.
+    pub fn sample_rate(&self) -> f64 {
+        return self.sample_rate;
+    }
+
+    /// Builds a record logged by the logger itself on behalf of this one, such as a repeat or suppression summary.
+    pub(crate) fn summary<'b>(&self, level: Level, module_path: &'b str, message: String) -> Record<'b>
+    where
+        'a: 'b,
+    {
+        return Record {
+            level,
+            timestamp: Utc::now(),
+            module_path,
+            target: module_path,
+            file: self.file,
+            line: self.line,
+            column: self.column,
+            thread: self.thread.clone(),
+            args: format_args!(""),
+            message,
+            fields: &[],
+            error: None,
+            causes: vec![],
+            context: vec![],
+            span: String::new(),
+            backtrace: None,
+            sample_rate: self.sample_rate,
+        };
+    }
+}
+
+/// A user-supplied predicate deciding whether a record is logged.
+#[derive(Clone)]
+pub(crate) struct Filter(pub(crate) Arc<dyn Fn(&Record) -> bool + Send + Sync>);
+
+impl std::fmt::Debug for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f.write_str("Filter");
+    }
+}
+
+/// A user-supplied function rendering a record into the line written to the outputs.
+#[derive(Clone)]
+pub(crate) struct Formatter(pub(crate) Arc<dyn Fn(&Record) -> String + Send + Sync>);
+
+impl std::fmt::Debug for Formatter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f.write_str("Formatter");
+    }
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kv::Value;
+
+    #[test]
+    fn test_record_captures_callsite() {
+        let line = line!() + 1;
+        let record = Record::new(Level::Info, module_path!(), format_args!("hello world"));
+
+        assert_eq!(record.target(), module_path!());
+        assert_eq!(record.file(), file!());
+        assert_eq!(record.line(), line);
+        assert_eq!(record.thread().id(), std::thread::current().id());
+        assert_eq!(record.args().to_string(), "hello world");
+    }
+
+    #[test]
+    fn test_record_builders() {
+        let error = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "read-only");
+        let fields = [("user_id", Value::Display(&42))];
+        let record = Record::new(Level::Error, "vendor::db", format_args!("write failed"))
+            .with_location("vendor/db.rs", 42)
+            .with_fields(&fields)
+            .with_error(&error);
+
+        assert_eq!(record.file(), "vendor/db.rs");
+        assert_eq!(record.line(), 42);
+        assert_eq!(record.column(), 0);
+        assert_eq!(record.fields().len(), 1);
+        assert_eq!(record.error().map(|e| e.to_string()), Some(String::from("read-only")));
+    }
+}
@@ -0,0 +1,233 @@
+use crate::logger::Level;
+use std::{
+    cell::Cell,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+thread_local! {
+    static RNG: Cell<u64> = Cell::new(seed());
+}
+
+/// How many log messages a `SampleRule` keeps.
+///
+/// # Variants
+///
+/// - `OneIn(n)`: Deterministically keeps the first of every `n` messages - `0` and `1` keep everything.
+/// - `Percent(p)`: Randomly keeps roughly `p` percent of messages - clamped to `0.0..=100.0`, and must be finite.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let every_tenth = SampleRate::OneIn(10);
+/// let a_quarter = SampleRate::Percent(25.0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum SampleRate {
+    OneIn(u32),
+    Percent(#[cfg_attr(feature = "serde", serde(deserialize_with = "finite"))] f64),
+}
+
+/// Deserializes a percentage, rejecting NaN and infinities like `SampleRule::new` does.
+#[cfg(feature = "serde")]
+fn finite<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let percent = <f64 as serde::Deserialize>::deserialize(deserializer)?;
+    if !percent.is_finite() {
+        return Err(serde::de::Error::custom(format!("`sample.rate.percent` must be a finite number, got {}", percent)));
+    }
+    return Ok(percent);
+}
+
+impl SampleRate {
+    /// The fraction of messages kept, from `0.0` to `1.0`.
+    pub fn fraction(&self) -> f64 {
+        return match self {
+            SampleRate::OneIn(n) => 1.0 / (*n).max(1) as f64,
+            SampleRate::Percent(p) => p.clamp(0.0, 100.0) / 100.0,
+        };
+    }
+}
+
+/// A sampling rule applied to log messages matching a level and/or module path.
+///
+/// Rules are checked in the order they were added and the first matching rule decides whether a message
+/// is kept. Messages that match no rule are always kept. A rule without a level matches every level and
+/// a rule without a module matches every module path.
+///
+/// # Fields
+/// - `level`: Optional level the rule applies to.
+/// - `module`: Optional module path prefix the rule applies to - `app::net` matches `app::net` and `app::net::tcp`.
+/// - `rate`: How many of the matching messages are kept.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let mut logger = Logger::new();
+/// logger.sample(SampleRule::new(SampleRate::OneIn(100)).level(Level::Trace)); // Keep 1 in 100 trace messages
+/// logger.sample(SampleRule::new(SampleRate::Percent(10.0)).module("app::net")); // Keep 10% of app::net messages
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(deny_unknown_fields))]
+pub struct SampleRule {
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub(crate) level: Option<Level>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub(crate) module: Option<String>,
+    pub(crate) rate: SampleRate,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    counter: Arc<AtomicU64>,
+}
+
+impl SampleRule {
+    /// Constructs a new `SampleRule` matching every message.
+    ///
+    /// # Arguments
+    /// * `rate` - How many of the matching messages are kept.
+    ///
+    /// # Panics
+    /// Panics if `rate` is a `Percent` which is NaN or infinite, since it would silently drop every message.
+    pub fn new(rate: SampleRate) -> Self {
+        if let SampleRate::Percent(p) = rate {
+            assert!(p.is_finite(), "SampleRate::Percent must be a finite number, got {}", p);
+        }
+
+        return Self {
+            level: None,
+            module: None,
+            rate,
+            counter: Arc::new(AtomicU64::new(0)),
+        };
+    }
+
+    /// Restricts the rule to messages of a single level.
+    ///
+    /// # Arguments
+    /// * `level` - The `Level` the rule applies to.
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = Some(level);
+        return self;
+    }
+
+    /// Restricts the rule to messages from a module and its submodules.
+    ///
+    /// # Arguments
+    /// * `module` - The module path prefix the rule applies to.
+    pub fn module(mut self, module: &str) -> Self {
+        self.module = Some(module.to_string());
+        return self;
+    }
+
+    fn matches(&self, level: &Level, module_path: &str) -> bool {
+        if matches!(&self.level, Some(l) if l != level) {
+            return false;
+        }
+
+        return match &self.module {
+            Some(module) => {
+                module_path == module
+                    || (module_path.starts_with(module.as_str()) && module_path[module.len()..].starts_with("::"))
+            }
+            None => true,
+        };
+    }
+
+    // `is_multiple_of` is newer than the minimum supported Rust version
+    #[allow(clippy::manual_is_multiple_of)]
+    fn keep(&self) -> bool {
+        return match self.rate {
+            SampleRate::OneIn(n) => self.counter.fetch_add(1, Ordering::Relaxed) % n.max(1) as u64 == 0,
+            SampleRate::Percent(_) => random() < self.rate.fraction(),
+        };
+    }
+}
+
+/// Decides whether a message is kept by the first matching rule.
+///
+/// # Returns
+/// Returns `Some(fraction)` with the sampling rate the message was kept at, or `None` if it was sampled out.
+pub(crate) fn sample(rules: &[SampleRule], level: &Level, module_path: &str) -> Option<f64> {
+    return match rules.iter().find(|rule| rule.matches(level, module_path)) {
+        Some(rule) if rule.keep() => Some(rule.rate.fraction()),
+        Some(_) => None,
+        None => Some(1.0),
+    };
+}
+
+/// Returns a random number in `0.0..1.0` from a thread-local xorshift generator.
+fn random() -> f64 {
+    return RNG.with(|rng| {
+        let mut x = rng.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        rng.set(x);
+        // Use the upper 53 bits for a uniformly distributed double
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    });
+}
+
+fn seed() -> u64 {
+    let seed = RandomState::new().build_hasher().finish();
+    // Xorshift must never be seeded with zero
+    return if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed };
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_in_n() {
+        let rules = vec![SampleRule::new(SampleRate::OneIn(3))];
+        let kept: Vec<Option<f64>> = (0..6).map(|_| sample(&rules, &Level::Trace, "app")).collect();
+
+        let third = Some(1.0 / 3.0);
+        assert_eq!(kept, vec![third, None, None, third, None, None]);
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let rules = vec![
+            SampleRule::new(SampleRate::Percent(0.0)).level(Level::Trace).module("app::net"),
+            SampleRule::new(SampleRate::Percent(100.0)).level(Level::Trace),
+        ];
+
+        assert_eq!(sample(&rules, &Level::Trace, "app::net"), None);
+        assert_eq!(sample(&rules, &Level::Trace, "app::net::tcp"), None);
+        assert_eq!(sample(&rules, &Level::Trace, "app::network"), Some(1.0));
+        assert_eq!(sample(&rules, &Level::Info, "app::net"), Some(1.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "SampleRate::Percent must be a finite number")]
+    fn test_non_finite_percent_is_rejected() {
+        let _ = SampleRule::new(SampleRate::Percent(f64::NAN));
+    }
+
+    #[test]
+    fn test_percent_is_roughly_honored() {
+        let rules = vec![SampleRule::new(SampleRate::Percent(25.0))];
+        let kept = (0..10_000).filter(|_| sample(&rules, &Level::Trace, "app").is_some()).count();
+
+        assert!((2_000..3_000).contains(&kept), "Kept {kept} of 10000 messages at 25%");
+    }
+
+    #[test]
+    fn test_fraction() {
+        assert_eq!(SampleRate::OneIn(0).fraction(), 1.0);
+        assert_eq!(SampleRate::OneIn(4).fraction(), 0.25);
+        assert_eq!(SampleRate::Percent(150.0).fraction(), 1.0);
+        assert_eq!(SampleRate::Percent(10.0).fraction(), 0.1);
+    }
+}
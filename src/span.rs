@@ -0,0 +1,220 @@
+use crate::{
+    kv::{Field, Value},
+    logger::{self, Level},
+};
+use std::{
+    cell::RefCell,
+    marker::PhantomData,
+    panic::Location,
+    time::{Duration, Instant},
+};
+
+thread_local! {
+    static SPANS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A timed section of code which logs its entry when created and its exit, with the elapsed time, when dropped.
+///
+/// Spans nest per thread: every message logged while a span is alive can show the path of open spans through
+/// the `{span}` placeholder, e.g. `startup/load_config`. Prefer creating spans with the `span!` macro.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// let mut logger = Logger::new();
+/// logger.log_format("[{timestamp} {level} {span}] {message}");
+///
+/// let _startup = span!(Level::Info, "startup"); // [... INFO startup] enter startup
+/// {
+///     let _config = span!(Level::Info, "load_config"); // [... INFO startup/load_config] enter load_config
+///     info!("reading logfather.toml"); // [... INFO startup/load_config] reading logfather.toml
+/// } // [... INFO startup/load_config] exit load_config elapsed=1.2ms
+/// ```
+#[must_use = "the span exits as soon as it is dropped"]
+#[derive(Debug)]
+pub struct Span {
+    level: Level,
+    module_path: &'static str,
+    name: String,
+    location: &'static Location<'static>,
+    depth: usize,
+    start: Instant,
+    returned: Option<String>,
+    // The span stack is thread-local, so the span must stay on the thread that entered it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Span {
+    /// Enters a new span and logs its entry.
+    ///
+    /// # Arguments
+    /// * `level` - The severity level of the entry and exit messages.
+    /// * `module_path` - The module path where the span originates.
+    /// * `name` - The name of the span, shown in the `{span}` path.
+    #[track_caller]
+    pub fn enter(level: Level, module_path: &'static str, name: impl Into<String>) -> Self {
+        return Self::enter_fields(level, module_path, name, &[]);
+    }
+
+    /// Enters a new span and logs its entry with fields, such as the arguments of an instrumented function.
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn enter_fields(level: Level, module_path: &'static str, name: impl Into<String>, fields: &[Field]) -> Self {
+        let name = name.into();
+        let depth = SPANS.with(|spans| {
+            let mut spans = spans.borrow_mut();
+            spans.push(name.clone());
+            spans.len() - 1
+        });
+
+        let span = Self {
+            level,
+            module_path,
+            name,
+            location: Location::caller(),
+            depth,
+            start: Instant::now(),
+            returned: None,
+            _not_send: PhantomData,
+        };
+        log_entry(span.level.clone(), module_path, &span.name, fields, span.location);
+        return span;
+    }
+
+    /// Records the value returned from the span, logged with its exit - formatted only if its level is enabled.
+    #[doc(hidden)]
+    pub fn returned(&mut self, value: &dyn std::fmt::Debug) {
+        if logger::enabled(&self.level) {
+            self.returned = Some(format!("{:?}", value));
+        }
+    }
+
+    /// Returns the name of the span.
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let returned = self.returned.as_ref().map(|value| value as &dyn std::fmt::Display);
+        log_exit(self.level.clone(), self.module_path, &self.name, self.start.elapsed(), returned, self.location);
+        SPANS.with(|spans| spans.borrow_mut().truncate(self.depth));
+    }
+}
+
+/// Logs the entry of a span or instrumented function.
+#[doc(hidden)]
+pub fn log_entry(level: Level, module_path: &str, name: &str, fields: &[Field], location: &Location) {
+    logger::log_at(level, module_path, module_path, format_args!("enter {}", name), fields, None, location);
+}
+
+/// Logs the exit of a span or instrumented function with its elapsed time and, if recorded, its return value.
+#[doc(hidden)]
+pub fn log_exit(
+    level: Level,
+    module_path: &str,
+    name: &str,
+    elapsed: Duration,
+    returned: Option<&dyn std::fmt::Display>,
+    location: &Location,
+) {
+    let elapsed = Value::Debug(&elapsed);
+    let fields = match returned {
+        Some(value) => vec![("return", Value::Display(value)), ("elapsed", elapsed)],
+        None => vec![("elapsed", elapsed)],
+    };
+    logger::log_at(level, module_path, module_path, format_args!("exit {}", name), &fields, None, location);
+}
+
+/// Returns the path of the spans open on the current thread, separated by `/`.
+pub(crate) fn path() -> String {
+    return SPANS.with(|spans| spans.borrow().join("/"));
+}
+
+/// Enters a span which logs its entry now and its exit, with the elapsed time, when dropped.
+///
+/// # Example
+///
+/// ``` no_run
+/// use logfather::*;
+///
+/// fn load_config() {
+///     let _span = span!(Level::Info, "load_config");
+///     // ...
+/// } // Logs "exit load_config elapsed=..."
+/// ```
+#[macro_export]
+macro_rules! span {
+    ($level:expr, $name:expr) => {
+        $crate::span::Span::enter($level, module_path!(), $name)
+    };
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans_nest() {
+        assert_eq!(path(), "");
+        let _outer = Span::enter(Level::Trace, module_path!(), "outer");
+        {
+            let inner = Span::enter(Level::Trace, module_path!(), "inner");
+            assert_eq!(inner.name(), "inner");
+            assert_eq!(path(), "outer/inner");
+        }
+        assert_eq!(path(), "outer");
+    }
+
+    #[cfg(feature = "instrument")]
+    #[logfather::instrument(level = "trace", skip(password))]
+    fn login(user: &str, password: &str) -> Result<usize, String> {
+        if password.is_empty() {
+            return Err(String::from("empty password"));
+        }
+        let length: usize = user.parse::<usize>().map_err(|e| e.to_string())?;
+        assert_eq!(path(), "login");
+        return Ok(length);
+    }
+
+    #[cfg(feature = "instrument")]
+    #[logfather::instrument(level = Level::Trace)]
+    fn first(values: &[u8]) -> &u8 {
+        &values[0]
+    }
+
+    #[cfg(feature = "instrument")]
+    #[logfather::instrument(level = "trace", skip(return))]
+    async fn double(value: u32) -> Result<u32, std::num::TryFromIntError> {
+        let value: u8 = value.try_into()?;
+        return Ok(u32::from(value) * 2);
+    }
+
+    #[cfg(feature = "instrument")]
+    #[test]
+    fn test_instrument() {
+        use std::{
+            future::Future,
+            task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+        };
+
+        assert_eq!(login("42", "hunter2"), Ok(42));
+        assert_eq!(login("42", ""), Err(String::from("empty password")));
+        assert!(login("alice", "hunter2").is_err());
+        assert_eq!(path(), "");
+        assert_eq!(first(&[7, 8]), &7);
+
+        // The future never waits, so a waker which does nothing is enough to drive it
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RawWaker::new(std::ptr::null(), &VTABLE), |_| {}, |_| {}, |_| {});
+        let waker = unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) };
+        let mut future = Box::pin(double(21));
+        fn assert_send(_: &impl Send) {}
+        assert_send(&future);
+        assert_eq!(future.as_mut().poll(&mut Context::from_waker(&waker)), Poll::Ready(Ok(42)));
+    }
+}
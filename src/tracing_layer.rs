@@ -0,0 +1,172 @@
+use crate::{
+    kv::{Field, Value},
+    logger::{self, Level},
+    record::Record,
+};
+use tracing_core::{
+    field::{Field as TracingField, Visit},
+    span, Event, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// A `tracing_subscriber::Layer` which forwards `tracing` events to the global logfather logger.
+///
+/// Events keep their target, module path and source location, and go through logfather's level filters,
+/// sampling, rate limiting and outputs like any other message. The names of the spans an event happened in
+/// fill the `{span}` placeholder, e.g. `request/query`, and the fields recorded on those spans are rendered
+/// ahead of the event's own fields.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::tracing_layer::TracingLayer;
+/// use tracing_subscriber::layer::SubscriberExt;
+///
+/// let subscriber = tracing_subscriber::registry().with(TracingLayer);
+/// tracing::subscriber::set_global_default(subscriber).expect("Another subscriber is installed");
+///
+/// let span = tracing::info_span!("request", id = 7);
+/// let _entered = span.enter();
+/// tracing::info!(status = 200, "served"); // [... INFO my_app] served id=7 status=200
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TracingLayer;
+
+/// The fields recorded on a span, stored in its extensions.
+struct SpanFields(Vec<(String, String)>);
+
+impl<S> Layer<S> for TracingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanFields(visitor.fields));
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        values.record(&mut visitor);
+        if let Some(span) = ctx.span(id) {
+            //A second `extensions_mut` while the first is still borrowed would deadlock
+            let mut extensions = span.extensions_mut();
+            match extensions.get_mut::<SpanFields>() {
+                Some(fields) => fields.0.extend(visitor.fields),
+                None => extensions.insert(SpanFields(visitor.fields)),
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = level(metadata.level());
+        if !logger::enabled(&level) {
+            return;
+        }
+
+        //Span fields come first, outermost span first, followed by the event's own
+        let mut names = vec![];
+        let mut visitor = FieldVisitor::default();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                names.push(span.name());
+                if let Some(fields) = span.extensions().get::<SpanFields>() {
+                    visitor.fields.extend(fields.0.iter().cloned());
+                }
+            }
+        }
+        event.record(&mut visitor);
+
+        let fields: Vec<Field> = visitor.fields.iter().map(|(k, v)| (k.as_str(), Value::Display(v))).collect();
+        forward(level, metadata, &fields, names.join("/"), format_args!("{}", visitor.message));
+    }
+}
+
+/// Logs a `tracing` event through the global logger.
+fn forward(level: Level, metadata: &tracing_core::Metadata, fields: &[Field], span: String, args: std::fmt::Arguments) {
+    let module_path = metadata.module_path().unwrap_or(metadata.target());
+    let mut record = Record::new(level, module_path, args).with_target(metadata.target()).with_fields(fields);
+    if let (Some(file), Some(line)) = (metadata.file(), metadata.line()) {
+        record = record.with_location(file, line);
+    }
+    record.span = span;
+    logger::log_record(record);
+}
+
+/// Maps a `tracing` level to the matching logfather `Level`.
+pub fn level(level: &tracing_core::Level) -> Level {
+    return if *level == tracing_core::Level::ERROR {
+        Level::Error
+    } else if *level == tracing_core::Level::WARN {
+        Level::Warning
+    } else if *level == tracing_core::Level::INFO {
+        Level::Info
+    } else if *level == tracing_core::Level::DEBUG {
+        Level::Debug
+    } else {
+        Level::Trace
+    };
+}
+
+/// Collects the `message` and the remaining fields of a span or event as text.
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &TracingField, value: &str) {
+        match field.name() {
+            "message" => self.message = value.to_string(),
+            name => self.fields.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    fn record_debug(&mut self, field: &TracingField, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{:?}", value),
+            name => self.fields.push((name.to_string(), format!("{:?}", value))),
+        }
+    }
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_mapping() {
+        assert_eq!(level(&tracing_core::Level::ERROR), Level::Error);
+        assert_eq!(level(&tracing_core::Level::WARN), Level::Warning);
+        assert_eq!(level(&tracing_core::Level::INFO), Level::Info);
+        assert_eq!(level(&tracing_core::Level::DEBUG), Level::Debug);
+        assert_eq!(level(&tracing_core::Level::TRACE), Level::Trace);
+    }
+
+    #[test]
+    fn test_span_fields_reach_records() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let seen = crate::logger::tests::capture("tracing-test", || {
+            tracing::subscriber::with_default(tracing_subscriber::registry().with(TracingLayer), || {
+                let request = tracing::info_span!("request", id = 7, user = tracing::field::Empty);
+                let _request = request.enter();
+                request.record("user", "alice");
+
+                let query = tracing::info_span!("query");
+                let _query = query.enter();
+                tracing::warn!(target: "tracing-test", status = 503, "slow");
+            });
+        });
+
+        assert_eq!(seen.len(), 1);
+        assert!(seen[0].starts_with(&format!("tracing-test {}:", file!())), "{}", seen[0]);
+        assert!(seen[0].ends_with("[request/query] slow id=7 user=alice status=503"), "{}", seen[0]);
+    }
+}
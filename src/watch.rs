@@ -0,0 +1,199 @@
+use crate::{
+    error::*,
+    logger::{self, Logger},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+
+/// Loads a TOML configuration file into the global logger and reloads it whenever the file changes.
+///
+/// The file is loaded with `Logger::from_file` and applied right away, so an invalid file is reported to the caller.
+/// Its settings replace those of the global logger, while the filters, formatter and pending collapsed and rate limited
+/// messages set up in code are kept. A background thread then polls the file's modification time every `interval` and
+/// installs each valid edit in a single step, so messages never see a half-applied configuration. Invalid edits are
+/// reported with an error message and the previous configuration stays in place. A file that is briefly missing, as
+/// while an editor replaces it, is picked up again once it reappears.
+///
+/// Watching stops when the returned `ConfigWatcher` is dropped.
+///
+/// # Arguments
+/// * `path` - The path of the TOML file.
+/// * `interval` - How often the file's modification time is checked.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+/// use std::time::Duration;
+///
+/// let _watcher = logfather::watch_file("logfather.toml", Duration::from_secs(2)).expect("Invalid logger configuration");
+/// info!("edit logfather.toml to raise the level without a restart");
+/// ```
+pub fn watch_file(path: impl AsRef<Path>, interval: Duration) -> Result<ConfigWatcher, LogfatherError> {
+    let mut watched = Watched { path: path.as_ref().to_path_buf(), stamp: None };
+    watched.stamp = watched.current_stamp();
+    let loaded = Logger::from_file(&watched.path)?;
+    logger::update_logger(|logger| merge(logger, loaded));
+
+    let name = watched.path.display().to_string();
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = stop.clone();
+    std::thread::Builder::new()
+        .name(String::from("logfather-watch"))
+        .spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                std::thread::sleep(interval);
+                match watched.poll() {
+                    Some(Ok(loaded)) => {
+                        logger::update_logger(|logger| merge(logger, loaded));
+                        crate::info!(target: "logfather", "reloaded configuration from {}", watched.path.display());
+                    }
+                    Some(Err(e)) => {
+                        crate::error!(target: "logfather", "kept the previous configuration - {e}");
+                    }
+                    None => {}
+                }
+            }
+        })
+        .map_err(|e| LogfatherError::ConfigError(format!("could not start watching {name}: {e}")))?;
+
+    return Ok(ConfigWatcher { stop });
+}
+
+/// Keeps a configuration file watched by `watch_file` - dropping it stops the background thread.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Replaces the settings of `current` with those loaded from a file, keeping what only code can set up.
+fn merge(current: &mut Logger, loaded: Logger) {
+    // Destructured in full so a new setting can't be forgotten here
+    let Logger {
+        path,
+        terminal_output,
+        file_output,
+        output_level,
+        ignore,
+        file_ignore,
+        terminal_ignore,
+        log_format,
+        timezone,
+        timestamp_format,
+        styles,
+        rate_limit,
+        file_collapse,
+        terminal_collapse,
+        collapse_timeout,
+        sampling,
+        context_fields,
+        backtrace_level,
+        filters: _,
+        formatter: _,
+        standalone: _,
+        collapser: _,
+        limiter: _,
+    } = loaded;
+
+    current.path = path;
+    current.terminal_output = terminal_output;
+    current.file_output = file_output;
+    current.output_level = output_level;
+    current.ignore = ignore;
+    current.file_ignore = file_ignore;
+    current.terminal_ignore = terminal_ignore;
+    current.log_format = log_format;
+    current.timezone = timezone;
+    current.timestamp_format = timestamp_format;
+    current.styles = styles;
+    current.rate_limit = rate_limit;
+    current.file_collapse = file_collapse;
+    current.terminal_collapse = terminal_collapse;
+    current.collapse_timeout = collapse_timeout;
+    current.sampling = sampling;
+    current.context_fields = context_fields;
+    current.backtrace_level = backtrace_level;
+}
+
+/// A configuration file and the modification time and length it had when last loaded.
+struct Watched {
+    path: PathBuf,
+    stamp: Option<(SystemTime, u64)>,
+}
+
+impl Watched {
+    fn current_stamp(&self) -> Option<(SystemTime, u64)> {
+        let metadata = std::fs::metadata(&self.path).ok()?;
+        return Some((metadata.modified().ok()?, metadata.len()));
+    }
+
+    /// Loads the file if it changed since the last poll, or returns `None` when it did not or is missing.
+    fn poll(&mut self) -> Option<Result<Logger, LogfatherError>> {
+        let stamp = self.current_stamp();
+        if stamp.is_none() || stamp == self.stamp {
+            return None;
+        }
+
+        self.stamp = stamp;
+        return Some(Logger::from_file(&self.path));
+    }
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::Level;
+
+    #[test]
+    fn test_poll_reloads_changes() {
+        let path = std::env::temp_dir().join(format!("logfather-watch-{}.toml", std::process::id()));
+        std::fs::write(&path, "level = \"info\"").unwrap();
+
+        let mut watched = Watched { path: path.clone(), stamp: None };
+        watched.stamp = watched.current_stamp();
+        assert!(watched.poll().is_none());
+
+        std::fs::write(&path, "level = \"warning\"\nterminal = { enabled = false }").unwrap();
+        let logger = watched.poll().expect("the file changed").unwrap();
+        assert_eq!(logger.output_level, Level::Warning);
+        assert!(watched.poll().is_none());
+
+        std::fs::write(&path, "level = \"loud\"").unwrap();
+        let error = watched.poll().expect("the file changed").unwrap_err();
+        assert!(error.to_string().contains("line 1"), "{error}");
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(watched.poll().is_none());
+    }
+
+    #[test]
+    fn test_merge_keeps_code_settings() {
+        let mut current = Logger::standalone().filter(|record| record.target() != "noisy").formatter(|record| {
+            return record.message().to_string();
+        });
+        let collapser = current.collapser.clone();
+
+        let loaded: Logger = toml::from_str("level = \"error\"\nlog_format = \"{level} {message}\"").unwrap();
+        merge(&mut current, loaded);
+
+        assert_eq!(current.output_level, Level::Error);
+        assert_eq!(current.log_format, "{level} {message}");
+        assert_eq!(current.filters.len(), 1);
+        assert!(current.formatter.is_some() && current.standalone);
+        assert!(Arc::ptr_eq(&current.collapser, &collapser));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    error::LogfatherError,
+    logger::{self, Level},
+    record::Record,
+};
+use std::{io::Write, panic::Location};
+
+/// The longest line buffered before it is logged without waiting for its newline.
+const MAX_LINE: usize = 64 * 1024;
+
+/// An `io::Write` adapter logging every line written to it as a separate message.
+///
+/// Bytes are buffered until a newline arrives, and each complete line is then logged at the writer's level and target
+/// with any trailing `\r` removed. Lines are located where the writer was constructed, so every writer is rate limited
+/// on its own. Lines longer than 64 KiB are logged in pieces of at most that size, so output
+/// without newlines can't grow the buffer unbounded. Invalid UTF-8 is replaced rather than rejected. A trailing
+/// partial line is logged when the writer is dropped, while `flush` only flushes the logger's buffered outputs so
+/// lines are never split.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use logfather::*;
+/// use std::{io::Write, panic::Location};
+///
+/// let mut writer: Box<dyn Write + Send> = Box::new(LogWriter::new(Level::Warning, "vendor"));
+/// write!(writer, "disk almost full\nretrying").unwrap(); // [... WARNING vendor] disk almost full
+/// drop(writer); // [... WARNING vendor] retrying
+/// ```
+#[derive(Debug)]
+pub struct LogWriter {
+    level: Level,
+    target: String,
+    module_path: &'static str,
+    location: &'static Location<'static>,
+    buffer: Vec<u8>,
+}
+
+impl LogWriter {
+    /// Constructs a new `LogWriter` logging to the global logger.
+    ///
+    /// # Arguments
+    /// * `level` - The `Level` every line is logged at.
+    /// * `target` - The target every line is logged with.
+    #[track_caller]
+    pub fn new(level: Level, target: &str) -> Self {
+        return Self {
+            level,
+            target: target.to_string(),
+            module_path: "writer",
+            location: Location::caller(),
+            buffer: Vec::new(),
+        };
+    }
+
+    /// Sets the module path lines are logged with - `writer` by default.
+    pub(crate) fn module_path(mut self, module_path: &'static str) -> Self {
+        self.module_path = module_path;
+        return self;
+    }
+
+    /// Sets the source location lines are logged from - where the writer was constructed by default.
+    pub(crate) fn location(mut self, location: &'static Location<'static>) -> Self {
+        self.location = location;
+        return self;
+    }
+
+    fn emit(&self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        logger::log_record(
+            Record::new(self.level.clone(), self.module_path, format_args!("{}", line.strip_suffix('\r').unwrap_or(&line)))
+                .with_target(&self.target)
+                .with_location(self.location.file(), self.location.line())
+                .with_column(self.location.column()),
+        );
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+
+        if let Some(end) = self.buffer.iter().rposition(|byte| *byte == b'\n') {
+            let lines: Vec<u8> = self.buffer.drain(..=end).collect();
+            for line in lines[..end].split(|byte| *byte == b'\n') {
+                self.emit(line);
+            }
+        }
+
+        //Log overlong lines in pieces, cut where no character is split
+        while self.buffer.len() > MAX_LINE {
+            let end = (1..=MAX_LINE).rev().find(|end| self.buffer[*end] & 0xC0 != 0x80).unwrap_or(MAX_LINE);
+            let piece: Vec<u8> = self.buffer.drain(..end).collect();
+            self.emit(&piece);
+        }
+        return Ok(buf.len());
+    }
+
+    // `io::Error::other` needs a newer Rust than the crate's minimum supported version
+    #[allow(clippy::io_other_error)]
+    fn flush(&mut self) -> std::io::Result<()> {
+        return match logger::flush() {
+            Ok(()) => Ok(()),
+            Err(LogfatherError::IoError(e)) => Err(e),
+            Err(e) => Err(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())),
+        };
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        if !self.buffer.is_empty() {
+            let line = std::mem::take(&mut self.buffer);
+            self.emit(&line);
+        }
+    }
+}
+
+// ##################################################################### Test #####################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Logger, RateLimit};
+
+    #[test]
+    fn test_buffers_partial_lines() {
+        let mut writer = LogWriter::new(Level::Trace, "writer-test");
+        write!(writer, "first\r\nsec").unwrap();
+        assert_eq!(writer.buffer, b"sec");
+
+        write!(writer, "ond\n\nthird").unwrap();
+        assert_eq!(writer.buffer, b"third");
+
+        writer.write_all(b"\n").unwrap();
+        assert!(writer.buffer.is_empty());
+    }
+
+    #[test]
+    fn test_caps_long_lines() {
+        let mut writer = LogWriter::new(Level::Trace, "writer-test");
+        writer.write_all(&vec![b'a'; MAX_LINE * 2 + 10]).unwrap();
+        assert_eq!(writer.buffer.len(), 10);
+
+        //A character straddling the cap is kept whole for the next piece
+        let mut writer = LogWriter::new(Level::Trace, "writer-test");
+        let mut bytes = vec![b'a'; MAX_LINE - 1];
+        bytes.extend_from_slice("é".as_bytes());
+        writer.write_all(&bytes).unwrap();
+        assert_eq!(writer.buffer, "é".as_bytes());
+    }
+
+    #[test]
+    fn test_writers_are_rate_limited_apart() {
+        let logger = Logger::standalone().rate_limit(RateLimit::new(0, 1));
+        let seen = crate::logger::tests::capture_with(logger, "writer-", || {
+            let mut first = LogWriter::new(Level::Info, "writer-a");
+            let mut second = LogWriter::new(Level::Info, "writer-b");
+            writeln!(first, "from a\nagain from a").unwrap();
+            writeln!(second, "from b").unwrap();
+        });
+
+        let messages: Vec<&str> = seen.iter().map(|line| line.split_once(' ').unwrap().1).collect();
+        let line = line!() - 7;
+        assert_eq!(
+            messages,
+            vec![format!("{}:{} from a", file!(), line), format!("{}:{} from b", file!(), line + 1)]
+        );
+    }
+}